@@ -0,0 +1,6 @@
+#![no_std]
+
+//! Admin safeguards shared by the fund contracts
+
+pub mod multisig;
+pub mod timelock;
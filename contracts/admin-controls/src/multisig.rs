@@ -0,0 +1,115 @@
+//! M-of-N signer approvals for admin actions
+//!
+//! An action is identified by the sha256 of the XDR of
+//! `(function name, arguments after caller...)`. Signers approve that hash
+//! ahead of time and the approvals are consumed when the action runs.
+
+use soroban_sdk::{
+    log, symbol_short, xdr::ToXdr, Address, BytesN, Env, IntoVal, Symbol, Val, Vec,
+};
+
+// Storage keys
+const SIGNERS: Symbol = symbol_short!("SIGNERS");
+const SIGNER_THRESHOLD: Symbol = symbol_short!("SIG_THR");
+const APPROVALS: Symbol = symbol_short!("APPROVALS");
+
+/// Authorize an admin action
+///
+/// Without a signer set only `admin` may act. With one, the caller may be the
+/// admin or any signer, and the action's approvals are checked and consumed.
+pub fn require_admin<T: IntoVal<Env, Val>>(env: &Env, admin: &Address, caller: &Address, action: T) {
+    let signers = signers(env);
+    if caller != admin && !signers.contains(caller) {
+        panic!("Unauthorized");
+    }
+    caller.require_auth();
+    
+    let threshold = threshold(env);
+    if threshold == 0 {
+        return;
+    }
+    
+    let action_hash: BytesN<32> = env.crypto().sha256(&action.to_xdr(env)).into();
+    let key = (APPROVALS, action_hash);
+    let approvals: Vec<Address> = env.storage().persistent().get(&key).unwrap_or(Vec::new(env));
+    
+    // Approvals from removed signers no longer count
+    let mut approved = 0;
+    for signer in approvals.iter() {
+        if signers.contains(&signer) {
+            approved += 1;
+        }
+    }
+    if approved < threshold {
+        panic!("Insufficient approvals");
+    }
+    env.storage().persistent().remove(&key);
+}
+
+/// Replace the signer set; a threshold of 0 with no signers turns multisig off
+pub fn set_signers(env: &Env, signers: &Vec<Address>, threshold: u32) {
+    if threshold > signers.len() || (threshold == 0 && !signers.is_empty()) {
+        panic!("Invalid signer threshold");
+    }
+    for (i, signer) in signers.iter().enumerate() {
+        if signers.first_index_of(&signer) != Some(i as u32) {
+            panic!("Duplicate signer");
+        }
+    }
+    
+    env.storage().instance().set(&SIGNERS, signers);
+    env.storage().instance().set(&SIGNER_THRESHOLD, &threshold);
+    
+    log!(env, "Signers updated: count={}, threshold={}", signers.len(), threshold);
+}
+
+/// Record a signer's approval of an action hash and return the approval count
+pub fn approve(env: &Env, signer: &Address, action_hash: &BytesN<32>) -> u32 {
+    signer.require_auth();
+    
+    if !signers(env).contains(signer) {
+        panic!("Not a signer");
+    }
+    
+    let key = (APPROVALS, action_hash.clone());
+    let mut approvals = approvals(env, action_hash);
+    if approvals.contains(signer) {
+        panic!("Already approved");
+    }
+    approvals.push_back(signer.clone());
+    env.storage().persistent().set(&key, &approvals);
+    
+    log!(env, "Action approved: signer={}, approvals={}", signer, approvals.len());
+    
+    approvals.len()
+}
+
+/// Withdraw a signer's approval of an action that has not run
+pub fn revoke(env: &Env, signer: &Address, action_hash: &BytesN<32>) {
+    signer.require_auth();
+    
+    let mut approvals = approvals(env, action_hash);
+    let index = approvals
+        .first_index_of(signer)
+        .unwrap_or_else(|| panic!("Not approved"));
+    approvals.remove(index);
+    env.storage().persistent().set(&(APPROVALS, action_hash.clone()), &approvals);
+    
+    log!(env, "Approval revoked: signer={}", signer);
+}
+
+pub fn signers(env: &Env) -> Vec<Address> {
+    env.storage().instance().get(&SIGNERS).unwrap_or(Vec::new(env))
+}
+
+/// Approvals each admin action needs (0 when multisig is off)
+pub fn threshold(env: &Env) -> u32 {
+    env.storage().instance().get(&SIGNER_THRESHOLD).unwrap_or(0)
+}
+
+pub fn approvals(env: &Env, action_hash: &BytesN<32>) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&(APPROVALS, action_hash.clone()))
+        .unwrap_or(Vec::new(env))
+}
@@ -0,0 +1,129 @@
+//! Queue -> delay -> execute flow for parameter changes
+//!
+//! Each contract defines its own action enum and applies it on execution;
+//! this module stores queued actions, enforces the delay and lets the
+//! guardian cancel them.
+
+use soroban_sdk::{log, symbol_short, Address, Env, IntoVal, Symbol, TryFromVal, Val, Vec};
+
+// Storage keys
+const TIMELOCK_DELAY: Symbol = symbol_short!("TL_DELAY");
+const GUARDIAN: Symbol = symbol_short!("GUARDIAN");
+const NEXT_CHANGE_ID: Symbol = symbol_short!("NEXT_CHG");
+const PENDING_CHANGE: Symbol = symbol_short!("PEND_CHG");
+const PENDING_IDS: Symbol = symbol_short!("PEND_IDS");
+
+pub const DEFAULT_TIMELOCK_DELAY: u64 = 172800; // 2 days
+pub const MIN_TIMELOCK_DELAY: u64 = 3600; // 1 hour
+pub const MAX_TIMELOCK_DELAY: u64 = 2592000; // 30 days
+
+/// Action waiting for the timelock delay to pass
+pub struct Queued<A> {
+    pub id: u64,
+    pub action: A,
+    pub queued_at: u64,
+    pub eta: u64,
+}
+
+/// Queue an action and return the id to execute it with
+pub fn queue<A: IntoVal<Env, Val>>(env: &Env, action: A) -> u64 {
+    let id: u64 = env.storage().instance().get(&NEXT_CHANGE_ID).unwrap_or(1);
+    env.storage().instance().set(&NEXT_CHANGE_ID, &(id + 1));
+    
+    let queued_at = env.ledger().timestamp();
+    let eta = queued_at + delay(env);
+    let action: Val = action.into_val(env);
+    env.storage().persistent().set(&(PENDING_CHANGE, id), &(action, queued_at, eta));
+    
+    let mut ids = pending_ids(env);
+    ids.push_back(id);
+    env.storage().instance().set(&PENDING_IDS, &ids);
+    
+    log!(env, "Change queued: id={}, eta={}", id, eta);
+    
+    id
+}
+
+/// Get a queued action
+pub fn get<A: TryFromVal<Env, Val>>(env: &Env, id: u64) -> Queued<A> {
+    let (action, queued_at, eta): (A, u64, u64) = env
+        .storage()
+        .persistent()
+        .get(&(PENDING_CHANGE, id))
+        .unwrap_or_else(|| panic!("Unknown change"));
+    Queued {
+        id,
+        action,
+        queued_at,
+        eta,
+    }
+}
+
+/// Remove a queued action whose delay has passed and return it for execution
+pub fn take_ready<A: TryFromVal<Env, Val>>(env: &Env, id: u64) -> A {
+    let change: Queued<A> = get(env, id);
+    if env.ledger().timestamp() < change.eta {
+        panic!("Timelock not expired");
+    }
+    remove(env, id);
+    
+    change.action
+}
+
+/// Drop a queued action (guardian only, defaulting to `admin`)
+pub fn cancel(env: &Env, admin: &Address, caller: &Address, id: u64) {
+    if *caller != guardian(env, admin) {
+        panic!("Unauthorized");
+    }
+    caller.require_auth();
+    
+    if !env.storage().persistent().has(&(PENDING_CHANGE, id)) {
+        panic!("Unknown change");
+    }
+    remove(env, id);
+    
+    log!(env, "Queued change cancelled: id={}, guardian={}", id, caller);
+}
+
+/// Ids of the actions waiting in the timelock
+pub fn pending_ids(env: &Env) -> Vec<u64> {
+    env.storage().instance().get(&PENDING_IDS).unwrap_or(Vec::new(env))
+}
+
+/// Delay between queueing and executing an action
+pub fn delay(env: &Env) -> u64 {
+    env.storage().instance().get(&TIMELOCK_DELAY).unwrap_or(DEFAULT_TIMELOCK_DELAY)
+}
+
+/// Reject delays outside the allowed range
+pub fn validate_delay(delay: u64) {
+    if !(MIN_TIMELOCK_DELAY..=MAX_TIMELOCK_DELAY).contains(&delay) {
+        panic!("Invalid timelock delay");
+    }
+}
+
+/// Apply a new delay; queue it first so the change itself is timelocked
+pub fn set_delay(env: &Env, delay: u64) {
+    env.storage().instance().set(&TIMELOCK_DELAY, &delay);
+}
+
+/// Guardian allowed to cancel queued actions, defaulting to `admin`
+pub fn guardian(env: &Env, admin: &Address) -> Address {
+    env.storage().instance().get(&GUARDIAN).unwrap_or(admin.clone())
+}
+
+pub fn set_guardian(env: &Env, guardian: &Address) {
+    env.storage().instance().set(&GUARDIAN, guardian);
+    
+    log!(env, "Guardian set: {}", guardian);
+}
+
+fn remove(env: &Env, id: u64) {
+    env.storage().persistent().remove(&(PENDING_CHANGE, id));
+    
+    let mut ids = pending_ids(env);
+    if let Some(index) = ids.first_index_of(id) {
+        ids.remove(index);
+        env.storage().instance().set(&PENDING_IDS, &ids);
+    }
+}
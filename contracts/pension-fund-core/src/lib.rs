@@ -1,25 +1,150 @@
 #![no_std]
+#![allow(clippy::too_many_arguments)]
 
 mod test;
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, log, token, Address, Env, Map, String, Symbol, Vec,
-    symbol_short,
+    contract, contractclient, contractimpl, contracttype, log, token::TokenClient, Address,
+    BytesN, Env, IntoVal, Map, String, Symbol, Val, Vec, symbol_short,
 };
-use soroban_token_sdk::TokenClient;
+use admin_controls::{multisig, timelock};
 
 // Storage keys
 const ADMIN: Symbol = symbol_short!("ADMIN");
-const USER_DATA: Symbol = symbol_short!("USER_DATA");
 const FUND_CONFIG: Symbol = symbol_short!("FUND_CFG");
 const TOTAL_LOCKED: Symbol = symbol_short!("TOT_LOCK");
+const SLEEVE: Symbol = symbol_short!("SLEEVE");
+const SWITCH_POLICY: Symbol = symbol_short!("SWCH_POL");
+const PROFILE_HISTORY: Symbol = symbol_short!("PROF_HIST");
+const TRANCHES: Symbol = symbol_short!("TRANCHES");
+const RETIREMENT: Symbol = symbol_short!("RETIRE");
+const BENEFICIARIES: Symbol = symbol_short!("BENEF");
+const LAST_ACTIVE: Symbol = symbol_short!("LAST_ACT");
+const INHERIT_POLICY: Symbol = symbol_short!("INH_POL");
+const INHERIT_CLAIM: Symbol = symbol_short!("INH_CLM");
+const TREASURY: Symbol = symbol_short!("TREASURY");
+const FEE_ACCRUAL: Symbol = symbol_short!("FEE_ACCR");
+const TREASURY_LEDGER: Symbol = symbol_short!("TRSY_LDG");
+const PENALTY_REDISTRIBUTION: Symbol = symbol_short!("PEN_REDS");
+const REFERRER: Symbol = symbol_short!("REFERRER");
+const REFERRAL_STATS: Symbol = symbol_short!("REF_STAT");
+const REFERRAL_BUDGET: Symbol = symbol_short!("REF_BUDG");
+const REFERRAL_TIERS: Symbol = symbol_short!("REF_TIER");
+const REFERRAL_SEQUENCE: Symbol = symbol_short!("REF_SEQ");
+const REFERRAL_CODE: Symbol = symbol_short!("REF_CODE");
+const VOTING_POWER: Symbol = symbol_short!("VOTE_PWR");
+const TOTAL_VOTING_POWER: Symbol = symbol_short!("TOT_VOTE");
+const GOVERNANCE_CONFIG: Symbol = symbol_short!("GOV_CFG");
+const NEXT_PROPOSAL_ID: Symbol = symbol_short!("NEXT_PROP");
+const PROPOSAL: Symbol = symbol_short!("PROPOSAL");
+const VOTE_RECEIPT: Symbol = symbol_short!("VOTED");
+const RISK_MANAGER: Symbol = symbol_short!("RISK_MGR");
+const ORACLE: Symbol = symbol_short!("ORACLE");
+const DEPOSIT_ASSET: Symbol = symbol_short!("DEP_ASSET");
+const DEPOSIT_ASSETS: Symbol = symbol_short!("DEP_ASSTS");
+const KEEPER: Symbol = symbol_short!("KEEPER");
+const WITHDRAWAL_NOTICE: Symbol = symbol_short!("WD_NOTICE");
+const WITHDRAWAL_EPOCH: Symbol = symbol_short!("WD_EPOCH");
+const WITHDRAWAL_REQUEST: Symbol = symbol_short!("WD_REQ");
+const NEXT_REQUEST_ID: Symbol = symbol_short!("NEXT_REQ");
+const EPOCH_REQUESTS: Symbol = symbol_short!("EPOCH_REQ");
+const EPOCH_SETTLEMENT: Symbol = symbol_short!("EPOCH_SET");
+const QUEUED_SHARES: Symbol = symbol_short!("QUEUED_SH");
+const WITHDRAWAL_IDS: Symbol = symbol_short!("WD_IDS");
+const ACTIVITY: Symbol = symbol_short!("ACTIVITY");
+const MEMBER: Symbol = symbol_short!("MEMBER");
+const MEMBER_COUNT: Symbol = symbol_short!("MEMB_CNT");
+const MEMBER_TOTALS: Symbol = symbol_short!("MEMB_TOT");
+const MATCH_BUDGETS: Symbol = symbol_short!("MATCH_BDG");
+const DEPOSIT_LIMITS: Symbol = symbol_short!("DEP_LIMIT");
+const ANNUAL_CONTRIBUTIONS: Symbol = symbol_short!("ANN_CONTR");
+const COMPLIANCE_MODE: Symbol = symbol_short!("COMP_MODE");
+const COMPLIANCE_OFFICER: Symbol = symbol_short!("COMP_OFF");
+const ATTESTER: Symbol = symbol_short!("ATTESTER");
+const ATTESTATION: Symbol = symbol_short!("ATTEST");
+const DENYLIST: Symbol = symbol_short!("DENYLIST");
+const CLAIMS_RESERVED: Symbol = symbol_short!("CLM_RSRV");
+const HARDSHIP_APPROVER: Symbol = symbol_short!("HS_APPRV");
+const HARDSHIP_LIMIT: Symbol = symbol_short!("HS_LIMIT");
+const HARDSHIP_REQUEST: Symbol = symbol_short!("HS_REQ");
+const HARDSHIP_IDS: Symbol = symbol_short!("HS_IDS");
+const HARDSHIP_USAGE: Symbol = symbol_short!("HS_USAGE");
+const NEXT_HARDSHIP_ID: Symbol = symbol_short!("NEXT_HS");
+const CONTRIBUTION: Symbol = symbol_short!("CONTRIB");
+const EMPLOYER: Symbol = symbol_short!("EMPLOYER");
+const EMPLOYMENT: Symbol = symbol_short!("EMPLOYMT");
+const UNLOCK_BUCKET: Symbol = symbol_short!("UNLK_BKT");
+
+// Interval between annuity and drawdown installments
+const PAYOUT_INTERVAL: u64 = 2592000; // 30 days
+
+// Defaults until the admin sets an inheritance policy
+const DEFAULT_INACTIVITY_PERIOD: u64 = 63072000; // 2 years
+const DEFAULT_CLAIM_DELAY: u64 = 7776000; // 90 days
+const MAX_BENEFICIARIES: u32 = 10;
+
+// Minimum time between performance fee crystallizations of a sleeve
+const CRYSTALLIZATION_PERIOD: u64 = 7776000; // 90 days
+
+// Management fee accrual
+const SECONDS_PER_YEAR: u64 = 31536000;
+const MAX_MANAGEMENT_FEE: u32 = 200; // 2% per year
+
+// Configuration limits (basis points unless noted)
+const MAX_WITHDRAWAL_FEE: u32 = 1000; // 10%
+const MAX_PERFORMANCE_FEE: u32 = 3000; // 30%
+const MAX_EARLY_WITHDRAWAL_PENALTY: u32 = 5000; // 50%
+const MAX_REFERRAL_BONUS: u32 = 500; // 5%
+const MAX_SWITCHING_FEE: u32 = 200; // 2%
+const MAX_LOCK_PERIOD: u64 = 1576800000; // 50 years
+
+// Member governance defaults
+const DEFAULT_VOTING_PERIOD: u64 = 604800; // 7 days
+const DEFAULT_QUORUM: u32 = 2000; // 20% of invested principal
+const DEFAULT_APPROVAL_THRESHOLD: u32 = 5000; // more than 50% of votes cast
+
+// Minimum time a withdrawal request waits before its epoch can settle it
+const DEFAULT_WITHDRAWAL_NOTICE: u64 = 604800; // 7 days
+
+// Most withdrawal requests one settlement call processes
+const MAX_SETTLEMENT_BATCH: u32 = 50;
+
+// Shortest interval between recurring contributions
+const MIN_CONTRIBUTION_INTERVAL: u64 = 86400; // 1 day
+
+// Employer matching and vesting caps
+const MAX_MATCHING_RATIO: u32 = 10000; // 100% of the employee contribution
+const MAX_VESTING_CLIFF: u64 = 157680000; // 5 years
+
+// Unlock times are grouped by day so expired locks can drop out of the fund statistics
+const UNLOCK_BUCKET_SIZE: u64 = 86400; // 1 day
+
+// Oldest entries are dropped from a member's activity log beyond this many
+const MAX_ACTIVITY_ENTRIES: u32 = 200;
+
+// Largest spread charged on deposits and withdrawals in other assets
+const MAX_ASSET_SPREAD: u32 = 500; // 5%
+
+// Referrer chain length checked for loops
+const MAX_REFERRAL_DEPTH: u32 = 16;
+
+// Custom referral codes: 4-16 characters of A-Z and 0-9
+const MIN_REFERRAL_CODE_LEN: u32 = 4;
+const MAX_REFERRAL_CODE_LEN: u32 = 16;
+
+// Defaults until the admin sets a profile switch policy
+const DEFAULT_SWITCH_COOLDOWN: u64 = 2592000; // 30 days
+
+// Fixed-point scale for NAV per share (same 1e7 scale used for prices)
+const NAV_SCALE: i128 = 10000000;
 
 #[derive(Clone)]
 #[contracttype]
 pub struct UserAccount {
-    pub balance: i128,
+    pub balance: i128,               // value of `shares` at the sleeve's last NAV
+    pub shares: i128,                // shares held in the sleeve for `risk_profile`
     pub risk_profile: RiskProfile,
-    pub locked_until: u64,
+    pub locked_until: u64,           // unlock time of the last-maturing tranche
     pub last_deposit: u64,
     pub total_deposits: i128,
     pub total_withdrawals: i128,
@@ -27,7 +152,7 @@ pub struct UserAccount {
     pub referral_code: String,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[contracttype]
 pub enum RiskProfile {
     Conservative = 1,
@@ -46,6 +171,277 @@ pub struct FundConfig {
     pub performance_fee: u32, // basis points
     pub early_withdrawal_penalty: u32, // basis points
     pub referral_bonus: u32, // basis points
+    pub management_fee: u32, // basis points per year of sleeve value
+}
+
+/// Shares bought by a single deposit, locked until `unlock_at`
+#[derive(Clone)]
+#[contracttype]
+pub struct DepositTranche {
+    pub shares: i128,
+    pub principal: i128,
+    pub deposited_at: u64,
+    pub unlock_at: u64,
+    pub vests_at: u64,               // employer match forfeitable until then; 0 for own deposits
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum PayoutPlan {
+    LumpSum,
+    FixedMonthly(i128),              // KALE paid each interval until the balance runs out
+    Drawdown(u32),                   // basis points of the remaining balance each interval
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct RetirementPlan {
+    pub retirement_date: u64,
+    pub plan: PayoutPlan,
+    pub next_payout: u64,
+    pub payouts_made: u32,
+    pub total_paid: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Beneficiary {
+    pub address: Address,
+    pub share: u32,                  // basis points of the inherited balance
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct InheritancePolicy {
+    pub inactivity_period: u64,      // seconds without activity before a claim can start
+    pub claim_delay: u64,            // seconds the owner has to check in and cancel
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct InheritanceClaim {
+    pub claimant: Address,
+    pub initiated_at: u64,
+    pub executable_at: u64,
+}
+
+/// Sub-portfolio holding the pooled assets of every member on one risk profile
+#[derive(Clone)]
+#[contracttype]
+pub struct Sleeve {
+    pub profile: RiskProfile,
+    pub total_shares: i128,
+    pub total_value: i128,           // KALE-denominated value of the sleeve's holdings
+    pub last_valuation: u64,
+    pub high_water_mark: i128,       // highest NAV per share on which performance fees were charged
+    pub last_fee_accrual: u64,       // management fee is accrued up to this time
+}
+
+/// Fee shares minted to the treasury out of one sleeve
+#[derive(Clone)]
+#[contracttype]
+pub struct FeeAccrual {
+    pub profile: RiskProfile,
+    pub fee_shares: i128,            // sleeve shares currently owned by the treasury
+    pub performance_fees: i128,      // cumulative KALE value charged as performance fees
+    pub management_fees: i128,       // cumulative KALE value charged as management fees
+    pub last_crystallization: u64,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct ProfileSwitchPolicy {
+    pub cooldown: u64,               // seconds between profile changes
+    pub switching_fee: u32,          // basis points of the value moved
+}
+
+/// Compliance record of a member moving between sleeves
+#[derive(Clone)]
+#[contracttype]
+pub struct ProfileChange {
+    pub from_profile: RiskProfile,
+    pub to_profile: RiskProfile,
+    pub value_moved: i128,
+    pub fee: i128,
+    pub shares_burned: i128,
+    pub shares_issued: i128,
+    pub timestamp: u64,
+}
+
+/// KALE collected by the fund on its own account, held in the contract balance
+#[derive(Clone)]
+#[contracttype]
+pub struct TreasuryLedger {
+    pub withdrawal_fees: i128,
+    pub early_withdrawal_penalties: i128,
+    pub performance_fees: i128,      // performance and management fees realized from fee shares
+    pub switching_fees: i128,        // fees charged on risk profile changes
+    pub withdrawn: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct ReferralStats {
+    pub referred_members: u32,       // members who named this address on their first deposit
+    pub referred_volume: i128,       // deposits made by directly referred members
+    pub rewards_earned: i128,        // rewards received across all tiers
+}
+
+/// Fund configuration fields to change; `None` keeps the current value
+#[derive(Clone)]
+#[contracttype]
+pub struct ConfigUpdate {
+    pub min_deposit: Option<i128>,
+    pub max_deposit: Option<i128>,
+    pub withdrawal_fee: Option<u32>,
+    pub performance_fee: Option<u32>,
+    pub early_withdrawal_penalty: Option<u32>,
+    pub lock_period: Option<u64>,
+    pub referral_bonus: Option<u32>,
+}
+
+/// Fee fields members can vote to change; `None` keeps the current value
+#[derive(Clone)]
+#[contracttype]
+pub struct FeeUpdate {
+    pub withdrawal_fee: Option<u32>,
+    pub performance_fee: Option<u32>,
+    pub early_withdrawal_penalty: Option<u32>,
+    pub management_fee: Option<u32>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct GovernanceConfig {
+    pub voting_period: u64,          // seconds
+    pub quorum: u32,                 // basis points of total invested principal at the snapshot
+    pub approval_threshold: u32,     // basis points of votes cast that must be in favour
+    pub proposal_threshold: i128,    // invested principal needed to open a proposal
+}
+
+/// Contribution caps across deposits; 0 means no cap
+#[derive(Clone)]
+#[contracttype]
+pub struct DepositLimits {
+    pub member_lifetime_cap: i128,
+    pub member_annual_cap: i128,     // per year (`timestamp / SECONDS_PER_YEAR`)
+    pub fund_tvl_cap: i128,
+    pub conservative_capacity: i128,
+    pub moderate_capacity: i128,
+    pub aggressive_capacity: i128,
+}
+
+/// Room left under each cap for a member depositing into a sleeve (`i128::MAX` when uncapped)
+#[derive(Clone)]
+#[contracttype]
+pub struct DepositHeadroom {
+    pub member_lifetime: i128,
+    pub member_annual: i128,
+    pub fund: i128,
+    pub profile: i128,
+    pub available: i128,
+}
+
+/// A member's contributions in one year
+#[derive(Clone)]
+#[contracttype]
+pub struct AnnualContributions {
+    pub year: u64,
+    pub total: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum PendingAction {
+    UpdateConfig(ConfigUpdate),
+    UpdateFees(FeeUpdate),
+    SetGovernanceConfig(GovernanceConfig),
+    SetDepositLimits(DepositLimits),
+    SetManagementFee(u32),
+    SetProfileSwitchPolicy(ProfileSwitchPolicy),
+    SetInheritancePolicy(InheritancePolicy),
+    SetWithdrawalNotice(u64),
+    RegisterDepositAsset(Symbol, Address, u32),
+    SetReferralTiers(Vec<u32>),
+    SetPenaltyRedistribution(bool),
+    MigrateKaleToken(Address),
+    SetOracle(Address),
+    SetTreasury(Address),
+    SetRiskManager(Address),
+    SetHardshipLimit(i128),
+    SetTimelockDelay(u64),
+}
+
+/// Parameter change waiting for the timelock delay to pass
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingChange {
+    pub id: u64,
+    pub action: PendingAction,
+    pub queued_at: u64,
+    pub eta: u64,
+}
+
+// Mirrors `AssetAllocation` in the risk manager contract
+#[derive(Clone)]
+#[contracttype]
+pub struct AssetAllocation {
+    pub kale_percentage: u32,    // basis points (10000 = 100%)
+    pub btc_percentage: u32,     // basis points
+    pub usdc_percentage: u32,    // basis points
+    pub xlm_percentage: u32,     // basis points
+}
+
+#[contractclient(name = "RiskManagerClient")]
+pub trait RiskManagerInterface {
+    fn update_allocation(env: Env, caller: Address, profile: RiskProfile, allocation: AssetAllocation) -> u64;
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AllocationProposal {
+    pub profile: RiskProfile,
+    pub allocation: AssetAllocation,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum ProposalAction {
+    UpdateFees(FeeUpdate),
+    UpdateAllocation(AllocationProposal),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ProposalStatus {
+    Active = 1,
+    Defeated = 2,
+    Succeeded = 3,
+    Queued = 4,
+}
+
+/// Member vote on a fee or allocation change, weighted by invested principal at `snapshot_ledger`
+#[derive(Clone)]
+#[contracttype]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: Address,
+    pub action: ProposalAction,
+    pub snapshot_ledger: u32,
+    pub total_weight: i128,
+    pub votes_for: i128,
+    pub votes_against: i128,
+    pub end_time: u64,
+    pub status: ProposalStatus,
+    pub queued_change: u64,          // timelock change id once queued (0 before)
+}
+
+/// Voting power (invested principal) of an account from `ledger` onwards
+#[derive(Clone)]
+#[contracttype]
+pub struct Checkpoint {
+    pub ledger: u32,
+    pub weight: i128,
 }
 
 #[derive(Clone)]
@@ -54,6 +450,7 @@ pub struct DepositResult {
     pub user: Address,
     pub amount: i128,
     pub new_balance: i128,
+    pub shares_issued: i128,
     pub lock_until: u64,
     pub referral_bonus: i128,
 }
@@ -65,10 +462,323 @@ pub struct WithdrawalResult {
     pub amount: i128,
     pub fee: i128,
     pub penalty: i128,
+    pub penalty_waived: i128,
     pub net_amount: i128,
+    pub shares_burned: i128,
+    pub new_balance: i128,
+}
+
+// Mirrors `PriceFeed` in the reflector adapter contract
+#[derive(Clone)]
+#[contracttype]
+pub struct PriceFeed {
+    pub asset: Symbol,
+    pub price_usd: i128,
+    pub price_xlm: Option<i128>,
+    pub timestamp: u64,
+    pub confidence: u32,
+    pub source: Symbol,
+}
+
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracleInterface {
+    fn get_fresh_price(env: Env, asset: Symbol) -> Option<PriceFeed>;
+}
+
+/// Non-KALE token members can deposit and withdraw in, valued in KALE at oracle prices
+#[derive(Clone)]
+#[contracttype]
+pub struct DepositAsset {
+    pub token: Address,
+    pub spread: u32,                 // basis points charged against the member both ways
+    pub balance: i128,               // tokens of this asset held for members
+    pub treasury: i128,              // tokens of this asset booked to the treasury from spreads
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AssetWithdrawalResult {
+    pub withdrawal: WithdrawalResult,
+    pub asset: Symbol,
+    pub asset_amount: i128,
+}
+
+/// Stage of a withdrawal request, carrying its payout once settled
+#[derive(Clone)]
+#[contracttype]
+pub enum WithdrawalStatus {
+    Queued,
+    Settled(WithdrawalResult),
+    Claimed(WithdrawalResult),
+}
+
+/// Queued withdrawal of sleeve shares, settled at the NAV of its epoch
+#[derive(Clone)]
+#[contracttype]
+pub struct WithdrawalRequest {
+    pub id: u64,
+    pub user: Address,
+    pub profile: RiskProfile,
+    pub shares: i128,
+    pub epoch: u32,
+    pub requested_at: u64,
+    pub status: WithdrawalStatus,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct EpochSettlement {
+    pub epoch: u32,
+    pub settled_at: u64,
+    pub nav_per_share: Map<RiskProfile, i128>,
+    pub requests_settled: u32,
+    pub total_payout: i128,
+    pub complete: bool,              // false while requests of the epoch remain to settle
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum HardshipReason {
+    Medical = 1,
+    Disability = 2,
+    Unemployment = 3,
+    Housing = 4,
+    Bereavement = 5,
+    Other = 6,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum HardshipStatus {
+    Pending = 1,
+    Approved = 2,
+    Rejected = 3,
+    Executed = 4,
+}
+
+/// Member request to withdraw with the early withdrawal penalty waived, and its decision
+#[derive(Clone)]
+#[contracttype]
+pub struct HardshipRequest {
+    pub id: u64,
+    pub user: Address,
+    pub amount: i128,
+    pub reason: HardshipReason,
+    pub requested_at: u64,
+    pub status: HardshipStatus,
+    pub approver: Option<Address>,
+    pub waiver: u32,                 // basis points of the penalty waived
+    pub decided_at: u64,
+    pub penalty_waived: i128,
+}
+
+/// Penalty waived for a member in one year (`timestamp / SECONDS_PER_YEAR`)
+#[derive(Clone)]
+#[contracttype]
+pub struct HardshipUsage {
+    pub year: u64,
+    pub waived: i128,
+}
+
+/// Standing contribution pulled from the member's token allowance by the keeper
+#[derive(Clone)]
+#[contracttype]
+pub struct ContributionSchedule {
+    pub amount: i128,
+    pub interval: u64,               // seconds between contributions
+    pub risk_profile: RiskProfile,   // used until the member holds shares
+    pub next_due: u64,
+    pub end_date: u64,
+    pub paused: bool,
+    pub contributions_made: u32,
+    pub contributions_missed: u32,
+    pub total_contributed: i128,
+}
+
+/// Employer depositing for its staff and matching their contributions
+#[derive(Clone)]
+#[contracttype]
+pub struct EmployerAccount {
+    pub matching_ratio: u32,         // basis points of each employee contribution
+    pub vesting_cliff: u64,          // seconds from enrolment until matches vest
+    pub match_budget: i128,          // KALE available for matching
+    pub employees: u32,
+    pub total_contributed: i128,
+    pub total_matched: i128,
+    pub total_forfeited: i128,
+}
+
+/// Employee enrolment; matched shares are forfeited if employment ends before `vests_at`
+#[derive(Clone)]
+#[contracttype]
+pub struct Employment {
+    pub employer: Address,
+    pub enrolled_at: u64,
+    pub vests_at: u64,
+    pub matched_shares: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct EmployeeContribution {
+    pub employee: Address,
+    pub amount: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct EmployerDeposit {
+    pub employee: Address,
+    pub amount: i128,
+    pub matched: i128,
+    pub shares_issued: i128,
     pub new_balance: i128,
 }
 
+#[derive(Clone)]
+#[contracttype]
+pub struct DepositRequest {
+    pub user: Address,
+    pub amount: i128,
+    pub risk_profile: RiskProfile,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum BatchStatus {
+    Success = 1,
+    InvalidAmount = 2,
+    ProfileMismatch = 3,
+    InsufficientFunds = 4,
+    NothingToClaim = 5,
+    CapExceeded = 6,
+    Frozen = 7,
+    NotVerified = 8,
+}
+
+/// Outcome of one batch deposit item, carrying the deposit when it went through
+#[derive(Clone)]
+#[contracttype]
+pub enum BatchDepositStatus {
+    Deposited(DepositResult),
+    Failed(BatchStatus),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct BatchDepositResult {
+    pub user: Address,
+    pub status: BatchDepositStatus,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct BatchWithdrawalResult {
+    pub user: Address,
+    pub status: BatchStatus,
+    pub requests_paid: u32,
+    pub amount_paid: i128,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ActivityKind {
+    Deposit = 1,
+    Withdrawal = 2,
+    Fee = 3,
+    Penalty = 4,
+    Reward = 5,
+    ProfileChange = 6,
+    Forfeiture = 7,
+}
+
+/// One line of a member's activity log
+#[derive(Clone)]
+#[contracttype]
+pub struct ActivityEntry {
+    pub kind: ActivityKind,
+    pub amount: i128,
+    pub shares: i128,
+    pub nav_per_share: i128,         // NAV of the member's sleeve after the activity
+    pub timestamp: u64,
+}
+
+/// Page of a member's activity between two timestamps, with totals over the whole range
+#[derive(Clone)]
+#[contracttype]
+pub struct Statement {
+    pub user: Address,
+    pub from: u64,
+    pub to: u64,
+    pub entries: Vec<ActivityEntry>,
+    pub total_entries: u32,
+    pub next_start: Option<u32>,
+    pub deposits: i128,
+    pub withdrawals: i128,
+    pub fees: i128,
+    pub penalties: i128,
+    pub rewards: i128,
+    pub balance: i128,
+}
+
+/// Running totals over members with a position, kept for fund statistics
+#[derive(Clone)]
+#[contracttype]
+pub struct MemberTotals {
+    pub invested_members: u32,
+    pub locked_members: u32,         // invested members whose unlock day has not expired
+    pub unlock_time_sum: i128,       // sum of `locked_until` over those members
+    pub expired_day: u64,            // unlock buckets before this day are no longer counted
+}
+
+/// Invested members whose locks end on one day
+#[derive(Clone)]
+#[contracttype]
+pub struct UnlockBucket {
+    pub members: u32,
+    pub unlock_time_sum: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct FundStats {
+    pub member_count: u32,
+    pub invested_members: u32,
+    pub tvl_conservative: i128,
+    pub tvl_moderate: i128,
+    pub tvl_aggressive: i128,
+    pub total_value_locked: i128,
+    pub average_lock_remaining: u64, // mean over invested members of the time left on their lock
+    pub total_fees_collected: i128,
+}
+
+/// KALE the fund owes, by bucket, against the KALE it actually holds
+///
+/// `member_funds` is member money held in KALE (`TOTAL_LOCKED`); value held in
+/// other assets or deployed by the rebalancer is not in it. `surplus` is the
+/// token balance minus all buckets; a negative surplus is a shortfall.
+#[derive(Clone)]
+#[contracttype]
+pub struct Reconciliation {
+    pub member_funds: i128,
+    pub treasury: i128,
+    pub reward_reserve: i128,        // referral budget plus employer match budgets
+    pub pending_claims: i128,        // settled withdrawals not yet paid
+    pub expected_balance: i128,
+    pub token_balance: i128,
+    pub surplus: i128,
+}
+
+/// Verification of a member by a registered attester
+#[derive(Clone)]
+#[contracttype]
+pub struct Attestation {
+    pub attester: Address,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    pub revoked: bool,
+}
+
 #[contract]
 pub struct PensionFundContract;
 
@@ -88,6 +798,9 @@ impl PensionFundContract {
         early_withdrawal_penalty: u32,
         referral_bonus: u32,
     ) {
+        if env.storage().instance().has(&ADMIN) {
+            panic!("Already initialized");
+        }
         admin.require_auth();
         
         let config = FundConfig {
@@ -99,7 +812,9 @@ impl PensionFundContract {
             performance_fee,
             early_withdrawal_penalty,
             referral_bonus,
+            management_fee: 0,
         };
+        Self::validate_config(&config);
         
         env.storage().instance().set(&ADMIN, &admin);
         env.storage().instance().set(&FUND_CONFIG, &config);
@@ -135,198 +850,3894 @@ impl PensionFundContract {
         // Transfer KALE tokens from user to contract
         let token_client = TokenClient::new(&env, &config.kale_token);
         token_client.transfer(&user, &env.current_contract_address(), &amount);
+        Self::mark_active(&env, &user);
         
-        // Calculate referral bonus
-        let mut referral_bonus = 0i128;
-        if let Some(ref_addr) = referral {
-            referral_bonus = (amount * config.referral_bonus as i128) / 10000;
-            if referral_bonus > 0 {
-                token_client.transfer(&env.current_contract_address(), &ref_addr, &referral_bonus);
-            }
+        Self::credit_deposit(&env, &config, &user, amount, risk_profile, referral)
+    }
+    
+    /// Deposit for many members in one call
+    ///
+    /// Each member authorizes their own item. Items that would fail (amount out of
+    /// range, risk profile mismatch, insufficient token balance) are reported and
+    /// skipped so the rest of the batch still goes through.
+    pub fn batch_deposit(env: Env, deposits: Vec<DepositRequest>) -> Vec<BatchDepositResult> {
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let token_client = TokenClient::new(&env, &config.kale_token);
+        let contract_address = env.current_contract_address();
+        
+        let mut results = Vec::new(&env);
+        let mut total_deposited = 0;
+        for request in deposits.iter() {
+            let user = request.user.clone();
+            let user_account = Self::get_user_account(&env, &user);
+            
+            let status = if let Some(block) = Self::deposit_block(&env, &user) {
+                block
+            } else if request.amount < config.min_deposit || request.amount > config.max_deposit {
+                BatchStatus::InvalidAmount
+            } else if user_account.shares > 0 && user_account.risk_profile != request.risk_profile {
+                BatchStatus::ProfileMismatch
+            } else if token_client.balance(&user) < request.amount {
+                BatchStatus::InsufficientFunds
+            } else if request.amount
+                > Self::get_deposit_headroom(env.clone(), user.clone(), request.risk_profile).available
+            {
+                BatchStatus::CapExceeded
+            } else {
+                BatchStatus::Success
+            };
+            
+            let status = if status == BatchStatus::Success {
+                user.require_auth();
+                token_client.transfer(&user, &contract_address, &request.amount);
+                Self::mark_active(&env, &user);
+                total_deposited += request.amount;
+                BatchDepositStatus::Deposited(Self::credit_account(
+                    &env,
+                    &config,
+                    &user,
+                    request.amount,
+                    request.risk_profile,
+                    None,
+                ))
+            } else {
+                BatchDepositStatus::Failed(status)
+            };
+            results.push_back(BatchDepositResult { user, status });
         }
         
+        // One total locked update for the whole batch
+        Self::add_total_locked(&env, total_deposited);
+        
+        log!(&env, "Batch deposit: items={}, total={}", deposits.len(), total_deposited);
+        
+        results
+    }
+    
+    /// Add KALE to the budget that pays referral rewards
+    pub fn fund_referral_budget(env: Env, funder: Address, amount: i128) {
+        funder.require_auth();
+        
+        if amount <= 0 {
+            panic!("Invalid amount");
+        }
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let token_client = TokenClient::new(&env, &config.kale_token);
+        token_client.transfer(&funder, &env.current_contract_address(), &amount);
+        
+        let budget = Self::get_referral_budget(env.clone()) + amount;
+        env.storage().instance().set(&REFERRAL_BUDGET, &budget);
+        
+        log!(&env, "Referral budget funded: funder={}, amount={}, budget={}", funder, amount, budget);
+    }
+    
+    /// Queue new bonuses (basis points) paid to referrers beyond the first tier (admin only)
+    ///
+    /// Returns the id to pass to `execute_change` once the timelock delay has passed.
+    pub fn set_referral_tiers(env: Env, caller: Address, tier_bonuses: Vec<u32>) -> u64 {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_referral_tiers"), tier_bonuses.clone()),
+        );
+        
+        if tier_bonuses.len() >= MAX_REFERRAL_DEPTH {
+            panic!("Too many referral tiers");
+        }
+        let mut previous = MAX_REFERRAL_BONUS;
+        for bonus in tier_bonuses.iter() {
+            if bonus > MAX_REFERRAL_BONUS {
+                panic!("Referral bonus above cap");
+            }
+            // Deeper tiers never earn more than the tier above them
+            if bonus > previous {
+                panic!("Referral tiers must be descending");
+            }
+            previous = bonus;
+        }
+        
+        timelock::queue(&env, PendingAction::SetReferralTiers(tier_bonuses))
+    }
+    
+    /// Get the KALE remaining in the referral budget
+    pub fn get_referral_budget(env: Env) -> i128 {
+        env.storage().instance().get(&REFERRAL_BUDGET).unwrap_or(0)
+    }
+    
+    /// Get the bonuses paid to referrers beyond the first tier
+    pub fn get_referral_tiers(env: Env) -> Vec<u32> {
+        env.storage().instance().get(&REFERRAL_TIERS).unwrap_or(Vec::new(&env))
+    }
+    
+    /// Get the referrer recorded on a member's first deposit
+    pub fn get_referrer(env: Env, user: Address) -> Option<Address> {
+        env.storage().persistent().get(&(REFERRER, user))
+    }
+    
+    /// Get referral statistics for an address
+    pub fn get_referral_stats(env: Env, addr: Address) -> ReferralStats {
+        env.storage().persistent().get(&(REFERRAL_STATS, addr)).unwrap_or(ReferralStats {
+            referred_members: 0,
+            referred_volume: 0,
+            rewards_earned: 0,
+        })
+    }
+    
+    /// Resolve a referral code to the member who owns it
+    pub fn resolve_referral_code(env: Env, code: String) -> Address {
+        env.storage()
+            .persistent()
+            .get(&(REFERRAL_CODE, code))
+            .unwrap_or_else(|| panic!("Unknown referral code"))
+    }
+    
+    /// Deposit using a referral code instead of the referrer's address
+    pub fn deposit_with_code(
+        env: Env,
+        user: Address,
+        amount: i128,
+        risk_profile: RiskProfile,
+        code: String,
+    ) -> DepositResult {
+        let referrer = Self::resolve_referral_code(env.clone(), code);
+        Self::deposit(env, user, amount, risk_profile, Some(referrer))
+    }
+    
+    /// Replace a member's referral code with a unique human-readable one
+    ///
+    /// Codes are 4-16 characters of A-Z and 0-9. The "KPF" prefix is reserved for
+    /// the codes assigned automatically on a member's first deposit.
+    pub fn claim_referral_code(env: Env, user: Address, code: String) {
+        user.require_auth();
+        
+        let mut user_account = Self::get_user_account(&env, &user);
+        if user_account.total_deposits == 0 {
+            panic!("Not a member");
+        }
+        
+        let len = code.len();
+        if !(MIN_REFERRAL_CODE_LEN..=MAX_REFERRAL_CODE_LEN).contains(&len) {
+            panic!("Invalid referral code");
+        }
+        let mut buf = [0u8; MAX_REFERRAL_CODE_LEN as usize];
+        code.copy_into_slice(&mut buf[..len as usize]);
+        let chars = &buf[..len as usize];
+        if !chars.iter().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
+            panic!("Invalid referral code");
+        }
+        if chars.starts_with(b"KPF") {
+            panic!("Reserved referral code");
+        }
+        if env.storage().persistent().has(&(REFERRAL_CODE, code.clone())) {
+            panic!("Referral code taken");
+        }
+        
+        // Release the previous code so it no longer resolves to this member
+        env.storage()
+            .persistent()
+            .remove(&(REFERRAL_CODE, user_account.referral_code.clone()));
+        env.storage().persistent().set(&(REFERRAL_CODE, code.clone()), &user);
+        
+        user_account.referral_code = code.clone();
+        env.storage().persistent().set(&user, &user_account);
+        
+        log!(&env, "Referral code claimed: user={}, code={}", user, code);
+    }
+    
+    /// Withdraw KALE tokens from the pension fund
+    ///
+    /// Unlocked tranches are consumed first (oldest first); the early withdrawal
+    /// penalty only applies to the part of the withdrawal taken from locked tranches.
+    pub fn withdraw(env: Env, user: Address, amount: i128) -> WithdrawalResult {
+        user.require_auth();
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let result = Self::redeem(&env, &config, &user, amount, None, 0, 0);
+        Self::mark_active(&env, &user);
+        
+        // Transfer tokens back to user
+        let token_client = TokenClient::new(&env, &config.kale_token);
+        token_client.transfer(&env.current_contract_address(), &user, &result.net_amount);
+        Self::check_reserves(&env, &config);
+        
+        log!(
+            &env,
+            "Withdrawal: user={}, amount={}, fee={}, penalty={}, net_amount={}",
+            user,
+            amount,
+            result.fee,
+            result.penalty,
+            result.net_amount
+        );
+        
+        result
+    }
+    
+    /// Deposit a registered asset other than KALE
+    ///
+    /// The tokens are valued in KALE at fresh oracle prices less the asset's spread,
+    /// and shares are minted for that value as for a KALE deposit.
+    pub fn deposit_asset(
+        env: Env,
+        user: Address,
+        asset: Symbol,
+        amount: i128,
+        risk_profile: RiskProfile,
+        referral: Option<Address>,
+    ) -> DepositResult {
+        user.require_auth();
+        
+        if amount <= 0 {
+            panic!("Invalid deposit amount");
+        }
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let mut deposit_asset = Self::get_deposit_asset(env.clone(), asset.clone());
+        
+        let (asset_price, kale_price) = Self::asset_prices(&env, &asset);
+        let value = amount * asset_price * (10000 - deposit_asset.spread as i128) / (kale_price * 10000);
+        if value < config.min_deposit || value > config.max_deposit {
+            panic!("Invalid deposit amount");
+        }
+        
+        let token_client = TokenClient::new(&env, &deposit_asset.token);
+        token_client.transfer(&user, &env.current_contract_address(), &amount);
+        
+        // The spread stays with the fund as asset tokens owned by the treasury
+        let spread_amount = amount * deposit_asset.spread as i128 / 10000;
+        deposit_asset.balance += amount - spread_amount;
+        deposit_asset.treasury += spread_amount;
+        env.storage().persistent().set(&(DEPOSIT_ASSET, asset.clone()), &deposit_asset);
+        
+        log!(&env, "Asset deposit: user={}, asset={}, amount={}, value={}", user, asset, amount, value);
+        
+        Self::mark_active(&env, &user);
+        
+        // The tokens are held as the asset, so member funds held in KALE are unchanged
+        Self::credit_account(&env, &config, &user, value, risk_profile, referral)
+    }
+    
+    /// Withdraw `amount` of KALE value, paid out in a registered asset
+    ///
+    /// Fees and penalties apply as for a KALE withdrawal; the net value is converted
+    /// at fresh oracle prices less the asset's spread.
+    pub fn withdraw_asset(env: Env, user: Address, asset: Symbol, amount: i128) -> AssetWithdrawalResult {
+        user.require_auth();
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let mut deposit_asset = Self::get_deposit_asset(env.clone(), asset.clone());
+        let (asset_price, kale_price) = Self::asset_prices(&env, &asset);
+        
+        let result = Self::redeem(&env, &config, &user, amount, None, 0, 0);
+        Self::mark_active(&env, &user);
+        
+        // Only the fee and penalty leave member funds in KALE; the payout is in the asset
+        Self::add_total_locked(&env, result.net_amount);
+        
+        let gross_amount = result.net_amount * kale_price / asset_price;
+        let asset_amount = result.net_amount * kale_price * (10000 - deposit_asset.spread as i128)
+            / (asset_price * 10000);
+        if gross_amount > deposit_asset.balance {
+            panic!("Insufficient asset liquidity");
+        }
+        deposit_asset.balance -= gross_amount;
+        deposit_asset.treasury += gross_amount - asset_amount;
+        env.storage().persistent().set(&(DEPOSIT_ASSET, asset.clone()), &deposit_asset);
+        
+        let token_client = TokenClient::new(&env, &deposit_asset.token);
+        token_client.transfer(&env.current_contract_address(), &user, &asset_amount);
+        
+        log!(
+            &env,
+            "Asset withdrawal: user={}, asset={}, amount={}, net_amount={}, asset_amount={}",
+            user,
+            asset,
+            amount,
+            result.net_amount,
+            asset_amount
+        );
+        
+        AssetWithdrawalResult {
+            withdrawal: result,
+            asset,
+            asset_amount,
+        }
+    }
+    
+    /// Queue `shares` of the member's sleeve for withdrawal in the current epoch
+    ///
+    /// Queued shares stay invested but cannot be withdrawn another way. They are
+    /// redeemed when the epoch settles, once the notice period has passed. Requests
+    /// worth less than the minimum deposit must take all of the member's free shares.
+    pub fn request_withdrawal(env: Env, user: Address, shares: i128) -> WithdrawalRequest {
+        user.require_auth();
+        Self::require_not_frozen(&env, &user);
+        
+        let user_account = Self::get_user_account(&env, &user);
+        let queued = Self::get_queued_shares(env.clone(), user.clone());
+        let free_shares = user_account.shares - Self::held_shares(&env, &user);
+        if shares <= 0 || free_shares < shares {
+            panic!("Insufficient balance");
+        }
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let value = Self::value_of_shares(&Self::accrued_sleeve(&env, &user_account.risk_profile), shares);
+        if value <= 0 || (value < config.min_deposit && shares < free_shares) {
+            panic!("Withdrawal request too small");
+        }
+        
+        let id: u64 = env.storage().instance().get(&NEXT_REQUEST_ID).unwrap_or(1);
+        env.storage().instance().set(&NEXT_REQUEST_ID, &(id + 1));
+        
+        let epoch = Self::get_withdrawal_epoch(env.clone());
+        let request = WithdrawalRequest {
+            id,
+            user: user.clone(),
+            profile: user_account.risk_profile,
+            shares,
+            epoch,
+            requested_at: env.ledger().timestamp(),
+            status: WithdrawalStatus::Queued,
+        };
+        env.storage().persistent().set(&(WITHDRAWAL_REQUEST, id), &request);
+        env.storage().persistent().set(&(QUEUED_SHARES, user.clone()), &(queued + shares));
+        
+        let mut requests = Self::get_epoch_requests(&env, epoch);
+        requests.push_back(id);
+        env.storage().persistent().set(&(EPOCH_REQUESTS, epoch), &requests);
+        
+        let mut user_requests = Self::get_withdrawal_requests(env.clone(), user.clone());
+        user_requests.push_back(id);
+        env.storage().persistent().set(&(WITHDRAWAL_IDS, user.clone()), &user_requests);
+        Self::mark_active(&env, &user);
+        
+        log!(&env, "Withdrawal requested: id={}, user={}, shares={}, epoch={}", id, user, shares, epoch);
+        
+        request
+    }
+    
+    /// Cancel a withdrawal request that has not been settled
+    pub fn cancel_withdrawal_request(env: Env, user: Address, request_id: u64) {
+        user.require_auth();
+        
+        let request = Self::get_withdrawal_request(env.clone(), request_id);
+        if request.user != user {
+            panic!("Unauthorized");
+        }
+        if !matches!(request.status, WithdrawalStatus::Queued) {
+            panic!("Request already settled");
+        }
+        
+        Self::drop_queued_request(&env, &request);
+        Self::remove_withdrawal_id(&env, &user, request_id);
+        
+        log!(&env, "Withdrawal request cancelled: id={}, user={}", request_id, user);
+    }
+    
+    /// Settle the current withdrawal epoch (keeper or admin)
+    ///
+    /// Requests past their notice period are redeemed at the NAV recorded by the
+    /// epoch's first settlement call and reserved for claiming; younger requests, and
+    /// requests that cannot be redeemed right now, roll into the next epoch. Each call
+    /// settles at most `MAX_SETTLEMENT_BATCH` requests and the epoch advances once none
+    /// remain. The keeper must have liquidated enough holdings into KALE beforehand.
+    pub fn settle_withdrawal_epoch(env: Env, caller: Address) -> EpochSettlement {
+        let keeper: Option<Address> = env.storage().instance().get(&KEEPER);
+        if Some(caller.clone()) == keeper {
+            caller.require_auth();
+        } else {
+            Self::require_admin(
+                &env,
+                &caller,
+                (Symbol::new(&env, "settle_withdrawal_epoch"),),
+            );
+        }
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        Self::accrue_all_management_fees(&env, &config);
+        
+        let epoch = Self::get_withdrawal_epoch(env.clone());
+        let current_time = env.ledger().timestamp();
+        let notice = Self::get_withdrawal_notice(env.clone());
+        
+        // An epoch settled over several calls keeps the NAVs of its first call
+        let mut settlement = Self::get_epoch_settlement(env.clone(), epoch).unwrap_or_else(|| {
+            let mut nav_per_share = Map::new(&env);
+            for profile in [RiskProfile::Conservative, RiskProfile::Moderate, RiskProfile::Aggressive] {
+                nav_per_share.set(profile, Self::nav_per_share(&Self::get_sleeve_state(&env, &profile)));
+            }
+            EpochSettlement {
+                epoch,
+                settled_at: current_time,
+                nav_per_share,
+                requests_settled: 0,
+                total_payout: 0,
+                complete: false,
+            }
+        });
+        
+        let requests = Self::get_epoch_requests(&env, epoch);
+        let batch = requests.len().min(MAX_SETTLEMENT_BATCH);
+        let mut carried = Self::get_epoch_requests(&env, epoch + 1);
+        let mut requests_settled = 0;
+        let mut total_payout = 0;
+        for request_id in requests.slice(0..batch).iter() {
+            let mut request = Self::get_withdrawal_request(env.clone(), request_id);
+            
+            // Frozen members' requests wait until the account is released, and requests
+            // whose shares are worth nothing or no longer free wait for the next epoch
+            let sleeve = Self::get_sleeve_state(&env, &request.profile);
+            let nav = settlement.nav_per_share.get(request.profile).unwrap();
+            let amount = request.shares * nav / NAV_SCALE;
+            let user_account = Self::get_user_account(&env, &request.user);
+            let free_shares = user_account.shares - Self::held_shares(&env, &request.user) + request.shares;
+            if current_time < request.requested_at + notice
+                || Self::is_frozen(env.clone(), request.user.clone())
+                || amount <= 0
+                || amount > sleeve.total_value
+                || free_shares < request.shares
+            {
+                request.epoch = epoch + 1;
+                env.storage().persistent().set(&(WITHDRAWAL_REQUEST, request_id), &request);
+                carried.push_back(request_id);
+                continue;
+            }
+            
+            // Release the escrow so the shares can be redeemed
+            let queued = Self::get_queued_shares(env.clone(), request.user.clone());
+            env.storage()
+                .persistent()
+                .set(&(QUEUED_SHARES, request.user.clone()), &(queued - request.shares));
+            
+            let result = Self::redeem(&env, &config, &request.user, amount, Some(request.shares), 0, 0);
+            
+            total_payout += result.net_amount;
+            requests_settled += 1;
+            request.status = WithdrawalStatus::Settled(result);
+            env.storage().persistent().set(&(WITHDRAWAL_REQUEST, request_id), &request);
+        }
+        
+        // Payouts must be covered by KALE actually held, on top of every other reserve
+        let reserved: i128 = env.storage().instance().get(&CLAIMS_RESERVED).unwrap_or(0);
+        env.storage().instance().set(&CLAIMS_RESERVED, &(reserved + total_payout));
+        Self::check_reserves(&env, &config);
+        
+        let remaining = requests.slice(batch..requests.len());
+        if remaining.is_empty() {
+            env.storage().persistent().remove(&(EPOCH_REQUESTS, epoch));
+            env.storage().instance().set(&WITHDRAWAL_EPOCH, &(epoch + 1));
+        } else {
+            env.storage().persistent().set(&(EPOCH_REQUESTS, epoch), &remaining);
+        }
+        env.storage().persistent().set(&(EPOCH_REQUESTS, epoch + 1), &carried);
+        
+        settlement.settled_at = current_time;
+        settlement.requests_settled += requests_settled;
+        settlement.total_payout += total_payout;
+        settlement.complete = remaining.is_empty();
+        env.storage().persistent().set(&(EPOCH_SETTLEMENT, epoch), &settlement);
+        
+        log!(
+            &env,
+            "Withdrawal epoch settled: epoch={}, requests={}, total_payout={}, carried={}, remaining={}",
+            epoch,
+            requests_settled,
+            total_payout,
+            carried.len(),
+            remaining.len()
+        );
+        
+        settlement
+    }
+    
+    /// Pay out a settled withdrawal request
+    pub fn claim_withdrawal(env: Env, user: Address, request_id: u64) -> WithdrawalResult {
+        user.require_auth();
+        Self::require_not_frozen(&env, &user);
+        
+        let mut request = Self::get_withdrawal_request(env.clone(), request_id);
+        if request.user != user {
+            panic!("Unauthorized");
+        }
+        let result = match request.status {
+            WithdrawalStatus::Settled(result) => result,
+            _ => panic!("Request not claimable"),
+        };
+        
+        request.status = WithdrawalStatus::Claimed(result.clone());
+        env.storage().persistent().set(&(WITHDRAWAL_REQUEST, request_id), &request);
+        Self::remove_withdrawal_id(&env, &user, request_id);
+        
+        let reserved: i128 = env.storage().instance().get(&CLAIMS_RESERVED).unwrap_or(0);
+        env.storage().instance().set(&CLAIMS_RESERVED, &(reserved - result.net_amount));
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let token_client = TokenClient::new(&env, &config.kale_token);
+        token_client.transfer(&env.current_contract_address(), &user, &result.net_amount);
+        Self::mark_active(&env, &user);
+        
+        log!(&env, "Withdrawal claimed: id={}, user={}, net_amount={}", request_id, user, result.net_amount);
+        
+        result
+    }
+    
+    /// Pay out every settled withdrawal request of the given members (permissionless)
+    ///
+    /// Members with nothing settled are reported and skipped rather than failing the batch.
+    pub fn batch_process_withdrawals(env: Env, users: Vec<Address>) -> Vec<BatchWithdrawalResult> {
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let token_client = TokenClient::new(&env, &config.kale_token);
+        let contract_address = env.current_contract_address();
+        let mut reserved: i128 = env.storage().instance().get(&CLAIMS_RESERVED).unwrap_or(0);
+        
+        let mut results = Vec::new(&env);
+        for user in users.iter() {
+            if Self::is_frozen(env.clone(), user.clone()) {
+                results.push_back(BatchWithdrawalResult {
+                    user,
+                    status: BatchStatus::Frozen,
+                    requests_paid: 0,
+                    amount_paid: 0,
+                });
+                continue;
+            }
+            
+            let mut pending = Vec::new(&env);
+            let mut requests_paid = 0;
+            let mut amount_paid = 0;
+            for request_id in Self::get_withdrawal_requests(env.clone(), user.clone()).iter() {
+                let mut request = Self::get_withdrawal_request(env.clone(), request_id);
+                let result = match request.status {
+                    WithdrawalStatus::Settled(result) => result,
+                    _ => {
+                        pending.push_back(request_id);
+                        continue;
+                    }
+                };
+                amount_paid += result.net_amount;
+                requests_paid += 1;
+                request.status = WithdrawalStatus::Claimed(result);
+                env.storage().persistent().set(&(WITHDRAWAL_REQUEST, request_id), &request);
+            }
+            
+            let status = if requests_paid == 0 {
+                BatchStatus::NothingToClaim
+            } else {
+                env.storage().persistent().set(&(WITHDRAWAL_IDS, user.clone()), &pending);
+                token_client.transfer(&contract_address, &user, &amount_paid);
+                reserved -= amount_paid;
+                BatchStatus::Success
+            };
+            results.push_back(BatchWithdrawalResult {
+                user,
+                status,
+                requests_paid,
+                amount_paid,
+            });
+        }
+        env.storage().instance().set(&CLAIMS_RESERVED, &reserved);
+        
+        log!(&env, "Batch withdrawals processed: users={}", users.len());
+        
+        results
+    }
+    
+    /// Get the ids of a member's unclaimed withdrawal requests
+    pub fn get_withdrawal_requests(env: Env, user: Address) -> Vec<u64> {
+        env.storage().persistent().get(&(WITHDRAWAL_IDS, user)).unwrap_or(Vec::new(&env))
+    }
+    
+    /// Get a withdrawal request
+    pub fn get_withdrawal_request(env: Env, request_id: u64) -> WithdrawalRequest {
+        env.storage()
+            .persistent()
+            .get(&(WITHDRAWAL_REQUEST, request_id))
+            .unwrap_or_else(|| panic!("Unknown withdrawal request"))
+    }
+    
+    /// Get the shares a member has queued for withdrawal
+    pub fn get_queued_shares(env: Env, user: Address) -> i128 {
+        env.storage().persistent().get(&(QUEUED_SHARES, user)).unwrap_or(0)
+    }
+    
+    /// Get the epoch new withdrawal requests join
+    pub fn get_withdrawal_epoch(env: Env) -> u32 {
+        env.storage().instance().get(&WITHDRAWAL_EPOCH).unwrap_or(1)
+    }
+    
+    /// Get the NAVs and payouts of a settled epoch
+    pub fn get_epoch_settlement(env: Env, epoch: u32) -> Option<EpochSettlement> {
+        env.storage().persistent().get(&(EPOCH_SETTLEMENT, epoch))
+    }
+    
+    /// Get the KALE set aside for settled but unclaimed withdrawals
+    pub fn get_claims_reserved(env: Env) -> i128 {
+        env.storage().instance().get(&CLAIMS_RESERVED).unwrap_or(0)
+    }
+    
+    /// Get the notice period withdrawal requests wait before settlement
+    pub fn get_withdrawal_notice(env: Env) -> u64 {
+        env.storage().instance().get(&WITHDRAWAL_NOTICE).unwrap_or(DEFAULT_WITHDRAWAL_NOTICE)
+    }
+    
+    /// Queue a new notice period withdrawal requests wait before settlement (admin only)
+    ///
+    /// Returns the id to pass to `execute_change` once the timelock delay has passed.
+    pub fn set_withdrawal_notice(env: Env, caller: Address, notice: u64) -> u64 {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_withdrawal_notice"), notice));
+        
+        timelock::queue(&env, PendingAction::SetWithdrawalNotice(notice))
+    }
+    
+    /// Set the keeper that liquidates holdings and settles withdrawal epochs (admin only)
+    pub fn set_keeper(env: Env, caller: Address, keeper: Address) {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_keeper"), keeper.clone()));
+        
+        env.storage().instance().set(&KEEPER, &keeper);
+        
+        log!(&env, "Keeper set: {}", keeper);
+    }
+    
+    /// Get the keeper, if any
+    pub fn get_keeper(env: Env) -> Option<Address> {
+        env.storage().instance().get(&KEEPER)
+    }
+    
+    /// File a hardship withdrawal request for an approver to review
+    pub fn submit_hardship_request(env: Env, user: Address, amount: i128, reason: HardshipReason) -> u64 {
+        user.require_auth();
+        
+        if amount <= 0 {
+            panic!("Invalid amount");
+        }
+        
+        let id: u64 = env.storage().instance().get(&NEXT_HARDSHIP_ID).unwrap_or(1);
+        env.storage().instance().set(&NEXT_HARDSHIP_ID, &(id + 1));
+        
+        let request = HardshipRequest {
+            id,
+            user: user.clone(),
+            amount,
+            reason,
+            requested_at: env.ledger().timestamp(),
+            status: HardshipStatus::Pending,
+            approver: None,
+            waiver: 0,
+            decided_at: 0,
+            penalty_waived: 0,
+        };
+        env.storage().persistent().set(&(HARDSHIP_REQUEST, id), &request);
+        
+        let mut ids = Self::get_hardship_requests(env.clone(), user.clone());
+        ids.push_back(id);
+        env.storage().persistent().set(&(HARDSHIP_IDS, user.clone()), &ids);
+        Self::mark_active(&env, &user);
+        
+        log!(&env, "Hardship request filed: id={}, user={}, amount={}, reason={:?}", id, user, amount, reason);
+        
+        id
+    }
+    
+    /// Approve a hardship request with a penalty waiver in basis points, or reject it (approvers only)
+    pub fn decide_hardship_request(
+        env: Env,
+        approver: Address,
+        request_id: u64,
+        approve: bool,
+        waiver: u32,
+    ) -> HardshipRequest {
+        if !Self::is_hardship_approver(env.clone(), approver.clone()) {
+            panic!("Unauthorized");
+        }
+        approver.require_auth();
+        
+        let mut request = Self::get_hardship_request(env.clone(), request_id);
+        if request.status != HardshipStatus::Pending {
+            panic!("Request already decided");
+        }
+        if request.user == approver {
+            panic!("Cannot decide own request");
+        }
+        if waiver > 10000 {
+            panic!("Invalid waiver");
+        }
+        
+        request.status = if approve { HardshipStatus::Approved } else { HardshipStatus::Rejected };
+        request.approver = Some(approver.clone());
+        request.waiver = if approve { waiver } else { 0 };
+        request.decided_at = env.ledger().timestamp();
+        env.storage().persistent().set(&(HARDSHIP_REQUEST, request_id), &request);
+        
+        log!(
+            &env,
+            "Hardship request decided: id={}, approver={}, approved={}, waiver={}",
+            request_id,
+            approver,
+            approve,
+            request.waiver
+        );
+        
+        request
+    }
+    
+    /// Withdraw under an approved hardship request
+    ///
+    /// The approved waiver is applied to the early withdrawal penalty, but the KALE
+    /// waived is capped by what is left of the member's annual hardship limit.
+    pub fn execute_hardship_withdrawal(env: Env, user: Address, request_id: u64) -> WithdrawalResult {
+        user.require_auth();
+        
+        let mut request = Self::get_hardship_request(env.clone(), request_id);
+        if request.user != user {
+            panic!("Unauthorized");
+        }
+        if request.status != HardshipStatus::Approved {
+            panic!("Request not approved");
+        }
+        
+        let year = env.ledger().timestamp() / SECONDS_PER_YEAR;
+        let mut usage = Self::get_hardship_usage(env.clone(), user.clone());
+        if usage.year != year {
+            usage = HardshipUsage { year, waived: 0 };
+        }
+        let remaining = (Self::get_hardship_limit(env.clone()) - usage.waived).max(0);
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let result = Self::redeem(&env, &config, &user, request.amount, None, request.waiver, remaining);
+        Self::mark_active(&env, &user);
+        
+        usage.waived += result.penalty_waived;
+        env.storage().persistent().set(&(HARDSHIP_USAGE, user.clone()), &usage);
+        
+        request.status = HardshipStatus::Executed;
+        request.penalty_waived = result.penalty_waived;
+        env.storage().persistent().set(&(HARDSHIP_REQUEST, request_id), &request);
+        
+        let token_client = TokenClient::new(&env, &config.kale_token);
+        token_client.transfer(&env.current_contract_address(), &user, &result.net_amount);
+        Self::check_reserves(&env, &config);
+        
+        log!(
+            &env,
+            "Hardship withdrawal: id={}, user={}, penalty={}, penalty_waived={}",
+            request_id,
+            user,
+            result.penalty,
+            result.penalty_waived
+        );
+        
+        result
+    }
+    
+    /// Get a hardship request
+    pub fn get_hardship_request(env: Env, request_id: u64) -> HardshipRequest {
+        env.storage()
+            .persistent()
+            .get(&(HARDSHIP_REQUEST, request_id))
+            .unwrap_or_else(|| panic!("Unknown hardship request"))
+    }
+    
+    /// Get the ids of a member's hardship requests
+    pub fn get_hardship_requests(env: Env, user: Address) -> Vec<u64> {
+        env.storage().persistent().get(&(HARDSHIP_IDS, user)).unwrap_or(Vec::new(&env))
+    }
+    
+    /// Get the penalty waived for a member in their latest hardship year
+    pub fn get_hardship_usage(env: Env, user: Address) -> HardshipUsage {
+        env.storage()
+            .persistent()
+            .get(&(HARDSHIP_USAGE, user))
+            .unwrap_or(HardshipUsage { year: 0, waived: 0 })
+    }
+    
+    /// Get the KALE of penalty that can be waived per member per year (0 until set)
+    pub fn get_hardship_limit(env: Env) -> i128 {
+        env.storage().instance().get(&HARDSHIP_LIMIT).unwrap_or(0)
+    }
+    
+    /// Queue a new limit on the KALE of penalty waived per member per year (admin only)
+    ///
+    /// Returns the id to pass to `execute_change` once the timelock delay has passed.
+    pub fn set_hardship_limit(env: Env, caller: Address, annual_limit: i128) -> u64 {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_hardship_limit"), annual_limit));
+        
+        if annual_limit < 0 {
+            panic!("Invalid limit");
+        }
+        
+        timelock::queue(&env, PendingAction::SetHardshipLimit(annual_limit))
+    }
+    
+    /// Grant or revoke the hardship approver role (admin only)
+    pub fn set_hardship_approver(env: Env, caller: Address, approver: Address, enabled: bool) {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_hardship_approver"), approver.clone(), enabled),
+        );
+        
+        if enabled {
+            env.storage().persistent().set(&(HARDSHIP_APPROVER, approver.clone()), &true);
+        } else {
+            env.storage().persistent().remove(&(HARDSHIP_APPROVER, approver.clone()));
+        }
+        
+        log!(&env, "Hardship approver updated: approver={}, enabled={}", approver, enabled);
+    }
+    
+    /// Check whether an address holds the hardship approver role
+    pub fn is_hardship_approver(env: Env, approver: Address) -> bool {
+        env.storage().persistent().has(&(HARDSHIP_APPROVER, approver))
+    }
+    
+    /// Set up (or replace) a standing contribution
+    ///
+    /// The member must `approve` this contract to spend the contributions on the
+    /// KALE token; the keeper then collects one contribution per interval.
+    pub fn set_contribution_schedule(
+        env: Env,
+        user: Address,
+        amount: i128,
+        interval: u64,
+        risk_profile: RiskProfile,
+        start: u64,
+        end_date: u64,
+    ) -> ContributionSchedule {
+        user.require_auth();
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        if amount < config.min_deposit || amount > config.max_deposit {
+            panic!("Invalid deposit amount");
+        }
+        if interval < MIN_CONTRIBUTION_INTERVAL {
+            panic!("Interval too short");
+        }
+        if end_date <= start || end_date <= env.ledger().timestamp() {
+            panic!("Invalid schedule dates");
+        }
+        
+        let schedule = ContributionSchedule {
+            amount,
+            interval,
+            risk_profile,
+            next_due: start.max(env.ledger().timestamp()),
+            end_date,
+            paused: false,
+            contributions_made: 0,
+            contributions_missed: 0,
+            total_contributed: 0,
+        };
+        env.storage().persistent().set(&(CONTRIBUTION, user.clone()), &schedule);
+        Self::mark_active(&env, &user);
+        
+        log!(
+            &env,
+            "Contribution schedule set: user={}, amount={}, interval={}, end_date={}",
+            user,
+            amount,
+            interval,
+            end_date
+        );
+        
+        schedule
+    }
+    
+    /// Collect a due contribution from the member's allowance (keeper or admin)
+    ///
+    /// Periods that passed without a collection, or whose collection failed for lack
+    /// of allowance or balance, a deposit cap or a compliance block, are counted as missed.
+    /// Returns `None` when the due contribution was missed.
+    pub fn execute_contribution(env: Env, caller: Address, user: Address) -> Option<DepositResult> {
+        let keeper: Option<Address> = env.storage().instance().get(&KEEPER);
+        if Some(caller.clone()) == keeper {
+            caller.require_auth();
+        } else {
+            Self::require_admin(
+                &env,
+                &caller,
+                (Symbol::new(&env, "execute_contribution"), user.clone()),
+            );
+        }
+        
+        let mut schedule = Self::get_contribution_schedule(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No contribution schedule"));
+        let current_time = env.ledger().timestamp();
+        if schedule.paused {
+            panic!("Contributions paused");
+        }
+        if current_time < schedule.next_due || schedule.next_due > schedule.end_date {
+            panic!("Contribution not due");
+        }
+        
+        // Only the latest due period is collected; earlier uncollected ones are missed
+        let skipped = ((current_time.min(schedule.end_date) - schedule.next_due) / schedule.interval) as u32;
+        schedule.contributions_missed += skipped;
+        schedule.next_due += (skipped as u64 + 1) * schedule.interval;
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let token_client = TokenClient::new(&env, &config.kale_token);
+        let contract_address = env.current_contract_address();
+        let user_account = Self::get_user_account(&env, &user);
+        let risk_profile = if user_account.shares > 0 {
+            user_account.risk_profile
+        } else {
+            schedule.risk_profile
+        };
+        
+        // A contribution that would breach a deposit cap is missed like an unfunded one
+        let funded = Self::deposit_block(&env, &user).is_none()
+            && token_client.allowance(&user, &contract_address) >= schedule.amount
+            && token_client.balance(&user) >= schedule.amount
+            && schedule.amount
+                <= Self::get_deposit_headroom(env.clone(), user.clone(), risk_profile).available;
+        
+        let result = if funded {
+            token_client.transfer_from(&contract_address, &user, &contract_address, &schedule.amount);
+            
+            let result = Self::credit_deposit(&env, &config, &user, schedule.amount, risk_profile, None);
+            schedule.contributions_made += 1;
+            schedule.total_contributed += schedule.amount;
+            Some(result)
+        } else {
+            schedule.contributions_missed += 1;
+            None
+        };
+        env.storage().persistent().set(&(CONTRIBUTION, user.clone()), &schedule);
+        
+        log!(
+            &env,
+            "Contribution executed: user={}, collected={}, made={}, missed={}",
+            user,
+            funded,
+            schedule.contributions_made,
+            schedule.contributions_missed
+        );
+        
+        result
+    }
+    
+    /// Pause a standing contribution; periods while paused are not counted as missed
+    pub fn pause_contributions(env: Env, user: Address) {
+        user.require_auth();
+        
+        let mut schedule = Self::get_contribution_schedule(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No contribution schedule"));
+        if schedule.paused {
+            panic!("Contributions paused");
+        }
+        schedule.paused = true;
+        env.storage().persistent().set(&(CONTRIBUTION, user.clone()), &schedule);
+        
+        log!(&env, "Contributions paused: {}", user);
+    }
+    
+    /// Resume a paused contribution; the next one falls due one interval from now
+    pub fn resume_contributions(env: Env, user: Address) {
+        user.require_auth();
+        
+        let mut schedule = Self::get_contribution_schedule(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No contribution schedule"));
+        if !schedule.paused {
+            panic!("Contributions not paused");
+        }
+        schedule.paused = false;
+        schedule.next_due = schedule.next_due.max(env.ledger().timestamp() + schedule.interval);
+        env.storage().persistent().set(&(CONTRIBUTION, user.clone()), &schedule);
+        Self::mark_active(&env, &user);
+        
+        log!(&env, "Contributions resumed: user={}, next_due={}", user, schedule.next_due);
+    }
+    
+    /// Cancel a standing contribution
+    pub fn cancel_contributions(env: Env, user: Address) {
+        user.require_auth();
+        
+        if Self::get_contribution_schedule(env.clone(), user.clone()).is_none() {
+            panic!("No contribution schedule");
+        }
+        env.storage().persistent().remove(&(CONTRIBUTION, user.clone()));
+        
+        log!(&env, "Contributions cancelled: {}", user);
+    }
+    
+    /// Get a member's standing contribution
+    pub fn get_contribution_schedule(env: Env, user: Address) -> Option<ContributionSchedule> {
+        env.storage().persistent().get(&(CONTRIBUTION, user))
+    }
+    
+    /// Register an employer with its matching ratio and vesting cliff (admin only)
+    pub fn register_employer(
+        env: Env,
+        caller: Address,
+        employer: Address,
+        matching_ratio: u32,
+        vesting_cliff: u64,
+    ) {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "register_employer"), employer.clone(), matching_ratio, vesting_cliff),
+        );
+        
+        if env.storage().persistent().has(&(EMPLOYER, employer.clone())) {
+            panic!("Employer already registered");
+        }
+        Self::validate_matching(matching_ratio, vesting_cliff);
+        
+        let account = EmployerAccount {
+            matching_ratio,
+            vesting_cliff,
+            match_budget: 0,
+            employees: 0,
+            total_contributed: 0,
+            total_matched: 0,
+            total_forfeited: 0,
+        };
+        env.storage().persistent().set(&(EMPLOYER, employer.clone()), &account);
+        
+        log!(
+            &env,
+            "Employer registered: employer={}, matching_ratio={}, vesting_cliff={}",
+            employer,
+            matching_ratio,
+            vesting_cliff
+        );
+    }
+    
+    /// Change the matching ratio and the vesting cliff for employees enrolled from now on
+    pub fn set_employer_matching(env: Env, employer: Address, matching_ratio: u32, vesting_cliff: u64) {
+        employer.require_auth();
+        
+        let mut account = Self::get_employer(env.clone(), employer.clone());
+        Self::validate_matching(matching_ratio, vesting_cliff);
+        account.matching_ratio = matching_ratio;
+        account.vesting_cliff = vesting_cliff;
+        env.storage().persistent().set(&(EMPLOYER, employer.clone()), &account);
+        
+        log!(
+            &env,
+            "Employer matching set: employer={}, matching_ratio={}, vesting_cliff={}",
+            employer,
+            matching_ratio,
+            vesting_cliff
+        );
+    }
+    
+    /// Add KALE to an employer's matching budget
+    pub fn fund_match_budget(env: Env, employer: Address, amount: i128) {
+        employer.require_auth();
+        
+        if amount <= 0 {
+            panic!("Invalid amount");
+        }
+        let mut account = Self::get_employer(env.clone(), employer.clone());
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let token_client = TokenClient::new(&env, &config.kale_token);
+        token_client.transfer(&employer, &env.current_contract_address(), &amount);
+        
+        account.match_budget += amount;
+        env.storage().persistent().set(&(EMPLOYER, employer.clone()), &account);
+        
+        let match_budgets: i128 = env.storage().instance().get(&MATCH_BUDGETS).unwrap_or(0);
+        env.storage().instance().set(&MATCH_BUDGETS, &(match_budgets + amount));
+        
+        log!(&env, "Match budget funded: employer={}, amount={}, budget={}", employer, amount, account.match_budget);
+    }
+    
+    /// Enrol an employee; the vesting cliff runs from now
+    pub fn enroll_employee(env: Env, employer: Address, employee: Address) -> Employment {
+        employer.require_auth();
+        
+        let mut account = Self::get_employer(env.clone(), employer.clone());
+        if Self::get_employment(env.clone(), employee.clone()).is_some() {
+            panic!("Employee already enrolled");
+        }
+        
+        let current_time = env.ledger().timestamp();
+        let employment = Employment {
+            employer: employer.clone(),
+            enrolled_at: current_time,
+            vests_at: current_time + account.vesting_cliff,
+            matched_shares: 0,
+        };
+        env.storage().persistent().set(&(EMPLOYMENT, employee.clone()), &employment);
+        
+        account.employees += 1;
+        env.storage().persistent().set(&(EMPLOYER, employer.clone()), &account);
+        
+        log!(&env, "Employee enrolled: employer={}, employee={}, vests_at={}", employer, employee, employment.vests_at);
+        
+        employment
+    }
+    
+    /// Deposit contributions for many employees in one call, adding the employer match
+    ///
+    /// The employer pays the contributions; matches come out of the match budget
+    /// (as far as it goes) and vest at the employee's cliff.
+    pub fn employer_deposit(
+        env: Env,
+        employer: Address,
+        contributions: Vec<EmployeeContribution>,
+    ) -> Vec<EmployerDeposit> {
+        employer.require_auth();
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let mut account = Self::get_employer(env.clone(), employer.clone());
+        
+        let mut total = 0;
+        for contribution in contributions.iter() {
+            if contribution.amount < config.min_deposit || contribution.amount > config.max_deposit {
+                panic!("Invalid deposit amount");
+            }
+            total += contribution.amount;
+        }
+        
+        let token_client = TokenClient::new(&env, &config.kale_token);
+        token_client.transfer(&employer, &env.current_contract_address(), &total);
+        
+        let current_time = env.ledger().timestamp();
+        let mut results = Vec::new(&env);
+        let mut total_matched = 0;
+        for contribution in contributions.iter() {
+            let employee = contribution.employee.clone();
+            let mut employment = Self::get_employment(env.clone(), employee.clone())
+                .unwrap_or_else(|| panic!("Employee not enrolled"));
+            if employment.employer != employer {
+                panic!("Employee not enrolled");
+            }
+            
+            // Employees keep the sleeve they chose; new accounts start conservative
+            let risk_profile = Self::get_user_account(&env, &employee).risk_profile;
+            let mut result =
+                Self::credit_deposit(&env, &config, &employee, contribution.amount, risk_profile, None);
+            let mut shares_issued = result.shares_issued;
+            
+            let matched = (contribution.amount * account.matching_ratio as i128 / 10000)
+                .min(account.match_budget);
+            if matched > 0 {
+                total_matched += matched;
+                account.match_budget -= matched;
+                account.total_matched += matched;
+                result = Self::credit_deposit(&env, &config, &employee, matched, risk_profile, None);
+                shares_issued += result.shares_issued;
+                if current_time < employment.vests_at {
+                    employment.matched_shares += result.shares_issued;
+                    env.storage().persistent().set(&(EMPLOYMENT, employee.clone()), &employment);
+                    
+                    // Tag the match's tranche so forfeiture burns exactly these shares
+                    let mut tranches: Vec<DepositTranche> =
+                        env.storage().persistent().get(&(TRANCHES, employee.clone())).unwrap();
+                    let last = tranches.len() - 1;
+                    let mut tranche = tranches.get(last).unwrap();
+                    tranche.vests_at = employment.vests_at;
+                    tranches.set(last, tranche);
+                    env.storage().persistent().set(&(TRANCHES, employee.clone()), &tranches);
+                }
+            }
+            
+            results.push_back(EmployerDeposit {
+                employee,
+                amount: contribution.amount,
+                matched,
+                shares_issued,
+                new_balance: result.new_balance,
+            });
+        }
+        
+        account.total_contributed += total;
+        env.storage().persistent().set(&(EMPLOYER, employer.clone()), &account);
+        
+        let match_budgets: i128 = env.storage().instance().get(&MATCH_BUDGETS).unwrap_or(0);
+        env.storage().instance().set(&MATCH_BUDGETS, &(match_budgets - total_matched));
+        
+        log!(&env, "Employer deposit: employer={}, employees={}, total={}", employer, contributions.len(), total);
+        
+        results
+    }
+    
+    /// End an employee's enrolment
+    ///
+    /// Before the vesting cliff the matched shares are burned and their value is
+    /// returned to the employer. Returns the KALE forfeited.
+    pub fn terminate_employee(env: Env, employer: Address, employee: Address) -> i128 {
+        employer.require_auth();
+        
+        let employment = Self::get_employment(env.clone(), employee.clone())
+            .unwrap_or_else(|| panic!("Employee not enrolled"));
+        if employment.employer != employer {
+            panic!("Employee not enrolled");
+        }
+        
+        let forfeited = Self::end_employment(&env, &employee, &employment);
+        
+        log!(&env, "Employee terminated: employer={}, employee={}, forfeited={}", employer, employee, forfeited);
+        
+        forfeited
+    }
+    
+    /// Get an employer account
+    pub fn get_employer(env: Env, employer: Address) -> EmployerAccount {
+        env.storage()
+            .persistent()
+            .get(&(EMPLOYER, employer))
+            .unwrap_or_else(|| panic!("Employer not registered"))
+    }
+    
+    /// Get an employee's enrolment, if any
+    pub fn get_employment(env: Env, employee: Address) -> Option<Employment> {
+        env.storage().persistent().get(&(EMPLOYMENT, employee))
+    }
+    
+    /// Queue registering or updating an asset accepted for deposits and withdrawals (admin only)
+    ///
+    /// Returns the id to pass to `execute_change` once the timelock delay has passed.
+    pub fn register_deposit_asset(env: Env, caller: Address, asset: Symbol, token: Address, spread: u32) -> u64 {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "register_deposit_asset"), asset.clone(), token.clone(), spread),
+        );
+        
+        if spread > MAX_ASSET_SPREAD {
+            panic!("Spread above cap");
+        }
+        if asset == symbol_short!("KALE") {
+            panic!("KALE is the base asset");
+        }
+        
+        timelock::queue(&env, PendingAction::RegisterDepositAsset(asset, token, spread))
+    }
+    
+    /// Get a registered deposit asset
+    pub fn get_deposit_asset(env: Env, asset: Symbol) -> DepositAsset {
+        env.storage()
+            .persistent()
+            .get(&(DEPOSIT_ASSET, asset))
+            .unwrap_or_else(|| panic!("Asset not registered"))
+    }
+    
+    /// Get the symbols of all registered deposit assets
+    pub fn get_deposit_assets(env: Env) -> Vec<Symbol> {
+        env.storage().instance().get(&DEPOSIT_ASSETS).unwrap_or(Vec::new(&env))
+    }
+    
+    /// Queue a new price oracle used to value other assets (admin only)
+    ///
+    /// Returns the id to pass to `execute_change` once the timelock delay has passed.
+    pub fn set_oracle(env: Env, caller: Address, oracle: Address) -> u64 {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_oracle"), oracle.clone()));
+        
+        timelock::queue(&env, PendingAction::SetOracle(oracle))
+    }
+    
+    /// Register a retirement date and payout plan
+    ///
+    /// The date cannot be in the past or before any of the member's tranches
+    /// unlocks. The plan can be changed until the first installment has been paid.
+    pub fn register_retirement(
+        env: Env,
+        user: Address,
+        retirement_date: u64,
+        plan: PayoutPlan,
+    ) -> RetirementPlan {
+        user.require_auth();
+        
+        if let Some(existing) = Self::get_retirement_plan(env.clone(), user.clone()) {
+            if existing.payouts_made > 0 {
+                panic!("Payouts already started");
+            }
+        }
+        
+        if retirement_date < env.ledger().timestamp() {
+            panic!("Retirement date in the past");
+        }
+        for tranche in Self::get_tranches(env.clone(), user.clone()).iter() {
+            if retirement_date < tranche.unlock_at {
+                panic!("Retirement date before lock expiry");
+            }
+        }
+        
+        match plan {
+            PayoutPlan::LumpSum => {}
+            PayoutPlan::FixedMonthly(amount) => {
+                if amount <= 0 {
+                    panic!("Invalid payout amount");
+                }
+            }
+            PayoutPlan::Drawdown(percentage) => {
+                if percentage == 0 || percentage > 10000 {
+                    panic!("Invalid drawdown percentage");
+                }
+            }
+        }
+        
+        let retirement_plan = RetirementPlan {
+            retirement_date,
+            plan,
+            next_payout: retirement_date,
+            payouts_made: 0,
+            total_paid: 0,
+        };
+        env.storage().persistent().set(&(RETIREMENT, user.clone()), &retirement_plan);
+        Self::mark_active(&env, &user);
+        
+        log!(&env, "Retirement registered: user={}, date={}", user, retirement_date);
+        
+        retirement_plan
+    }
+    
+    /// Release the member's due retirement installment (permissionless)
+    ///
+    /// Tranches deposited after registering and still locked pay the early
+    /// withdrawal penalty like any other withdrawal; the withdrawal fee applies.
+    pub fn process_payout(env: Env, user: Address) -> WithdrawalResult {
+        let mut retirement_plan = Self::get_retirement_plan(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No retirement plan"));
+        
+        let current_time = env.ledger().timestamp();
+        if current_time < retirement_plan.next_payout {
+            panic!("Payout not due");
+        }
+        
+        // Shares queued for withdrawal or not yet vested are not paid out
+        let balance = Self::free_balance(&env, &user);
+        let amount = match retirement_plan.plan {
+            PayoutPlan::LumpSum => balance,
+            PayoutPlan::FixedMonthly(installment) => installment.min(balance),
+            PayoutPlan::Drawdown(percentage) => (balance * percentage as i128) / 10000,
+        };
+        if amount <= 0 {
+            panic!("Nothing to pay out");
+        }
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let result = Self::redeem(&env, &config, &user, amount, None, 0, 0);
+        
+        retirement_plan.payouts_made += 1;
+        retirement_plan.total_paid += result.net_amount;
+        retirement_plan.next_payout += PAYOUT_INTERVAL;
+        env.storage().persistent().set(&(RETIREMENT, user.clone()), &retirement_plan);
+        
+        let token_client = TokenClient::new(&env, &config.kale_token);
+        token_client.transfer(&env.current_contract_address(), &user, &result.net_amount);
+        Self::check_reserves(&env, &config);
+        
+        log!(
+            &env,
+            "Retirement payout: user={}, installment={}, amount={}, net_amount={}",
+            user,
+            retirement_plan.payouts_made,
+            amount,
+            result.net_amount
+        );
+        
+        result
+    }
+    
+    /// Get a member's retirement plan
+    pub fn get_retirement_plan(env: Env, user: Address) -> Option<RetirementPlan> {
+        env.storage().persistent().get(&(RETIREMENT, user))
+    }
+    
+    /// Name the beneficiaries of a member's balance (shares must sum to 100%)
+    pub fn set_beneficiaries(env: Env, user: Address, beneficiaries: Vec<Beneficiary>) {
+        user.require_auth();
+        
+        if beneficiaries.is_empty() || beneficiaries.len() > MAX_BENEFICIARIES {
+            panic!("Invalid number of beneficiaries");
+        }
+        
+        let mut total_share = 0u32;
+        for beneficiary in beneficiaries.iter() {
+            if beneficiary.address == user {
+                panic!("Cannot name self as beneficiary");
+            }
+            if beneficiary.share == 0 {
+                panic!("Invalid beneficiary share");
+            }
+            total_share += beneficiary.share;
+        }
+        if total_share != 10000 {
+            panic!("Beneficiary shares must sum to 100%");
+        }
+        
+        env.storage().persistent().set(&(BENEFICIARIES, user.clone()), &beneficiaries);
+        Self::mark_active(&env, &user);
+        
+        log!(&env, "Beneficiaries set: user={}, count={}", user, beneficiaries.len());
+    }
+    
+    /// Check in as the account owner, cancelling any pending inheritance claim
+    pub fn heartbeat(env: Env, user: Address) {
+        user.require_auth();
+        
+        Self::mark_active(&env, &user);
+        
+        log!(&env, "Heartbeat: user={}", user);
+    }
+    
+    /// Start the time-locked inheritance of an inactive member's balance (beneficiaries only)
+    pub fn initiate_inheritance_claim(env: Env, beneficiary: Address, owner: Address) -> InheritanceClaim {
+        beneficiary.require_auth();
+        
+        let beneficiaries = Self::get_beneficiaries(env.clone(), owner.clone());
+        if !beneficiaries.iter().any(|b| b.address == beneficiary) {
+            panic!("Not a beneficiary");
+        }
+        if env.storage().persistent().has(&(INHERIT_CLAIM, owner.clone())) {
+            panic!("Claim already pending");
+        }
+        
+        let policy = Self::get_inheritance_policy(env.clone());
+        let current_time = env.ledger().timestamp();
+        if current_time < Self::get_last_activity(env.clone(), owner.clone()) + policy.inactivity_period {
+            panic!("Owner not inactive");
+        }
+        
+        let claim = InheritanceClaim {
+            claimant: beneficiary.clone(),
+            initiated_at: current_time,
+            executable_at: current_time + policy.claim_delay,
+        };
+        env.storage().persistent().set(&(INHERIT_CLAIM, owner.clone()), &claim);
+        
+        log!(
+            &env,
+            "Inheritance claim initiated: owner={}, claimant={}, executable_at={}",
+            owner,
+            beneficiary,
+            claim.executable_at
+        );
+        
+        claim
+    }
+    
+    /// Pay an inactive member's balance out to their beneficiaries (permissionless)
+    ///
+    /// Only possible once the claim delay has passed without the owner checking in.
+    /// Locked tranches are released without the early withdrawal penalty.
+    pub fn execute_inheritance_claim(env: Env, owner: Address) -> WithdrawalResult {
+        let claim: InheritanceClaim = env
+            .storage()
+            .persistent()
+            .get(&(INHERIT_CLAIM, owner.clone()))
+            .unwrap_or_else(|| panic!("No pending claim"));
+        
+        if env.ledger().timestamp() < claim.executable_at {
+            panic!("Claim delay not elapsed");
+        }
+        
+        // Queued withdrawals are cancelled, settled ones pass to the beneficiaries and
+        // unvested employer matches are forfeited, so every remaining share is free
+        let unclaimed = Self::release_withdrawal_requests(&env, &owner);
+        if let Some(employment) = Self::get_employment(env.clone(), owner.clone()) {
+            if Self::unvested_shares(&env, &owner) > 0 {
+                Self::end_employment(&env, &owner, &employment);
+            }
+        }
+        
+        let balance = Self::get_account(env.clone(), owner.clone()).balance;
+        if balance <= 0 && unclaimed == 0 {
+            panic!("Nothing to inherit");
+        }
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let mut result = if balance > 0 {
+            Self::redeem(&env, &config, &owner, balance, None, 10000, i128::MAX)
+        } else {
+            WithdrawalResult {
+                user: owner.clone(),
+                amount: 0,
+                fee: 0,
+                penalty: 0,
+                penalty_waived: 0,
+                net_amount: 0,
+                shares_burned: 0,
+                new_balance: 0,
+            }
+        };
+        result.net_amount += unclaimed;
+        
+        // Split the proceeds; the last beneficiary receives any rounding remainder
+        let beneficiaries = Self::get_beneficiaries(env.clone(), owner.clone());
+        let token_client = TokenClient::new(&env, &config.kale_token);
+        let mut paid = 0i128;
+        for (i, beneficiary) in beneficiaries.iter().enumerate() {
+            let amount = if i as u32 == beneficiaries.len() - 1 {
+                result.net_amount - paid
+            } else {
+                (result.net_amount * beneficiary.share as i128) / 10000
+            };
+            paid += amount;
+            if amount > 0 {
+                token_client.transfer(&env.current_contract_address(), &beneficiary.address, &amount);
+            }
+        }
+        Self::check_reserves(&env, &config);
+        
+        env.storage().persistent().remove(&(INHERIT_CLAIM, owner.clone()));
+        
+        log!(
+            &env,
+            "Inheritance executed: owner={}, amount={}, beneficiaries={}",
+            owner,
+            result.net_amount,
+            beneficiaries.len()
+        );
+        
+        result
+    }
+    
+    /// Get a member's beneficiaries
+    pub fn get_beneficiaries(env: Env, user: Address) -> Vec<Beneficiary> {
+        env.storage()
+            .persistent()
+            .get(&(BENEFICIARIES, user))
+            .unwrap_or(Vec::new(&env))
+    }
+    
+    /// Get the pending inheritance claim on a member's balance
+    pub fn get_inheritance_claim(env: Env, owner: Address) -> Option<InheritanceClaim> {
+        env.storage().persistent().get(&(INHERIT_CLAIM, owner))
+    }
+    
+    /// Get the timestamp of a member's last deposit, withdrawal or check-in
+    pub fn get_last_activity(env: Env, user: Address) -> u64 {
+        env.storage().persistent().get(&(LAST_ACTIVE, user)).unwrap_or(0)
+    }
+    
+    /// Get the inactivity period and claim delay for inheritance
+    pub fn get_inheritance_policy(env: Env) -> InheritancePolicy {
+        env.storage().instance().get(&INHERIT_POLICY).unwrap_or(InheritancePolicy {
+            inactivity_period: DEFAULT_INACTIVITY_PERIOD,
+            claim_delay: DEFAULT_CLAIM_DELAY,
+        })
+    }
+    
+    /// Queue a new inactivity period and claim delay for inheritance (admin only)
+    ///
+    /// Returns the id to pass to `execute_change` once the timelock delay has passed.
+    pub fn set_inheritance_policy(env: Env, caller: Address, inactivity_period: u64, claim_delay: u64) -> u64 {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_inheritance_policy"), inactivity_period, claim_delay),
+        );
+        
+        // A zero period would let heirs claim the balance of an active member
+        if inactivity_period == 0 || claim_delay == 0 {
+            panic!("Invalid inheritance policy");
+        }
+        
+        timelock::queue(
+            &env,
+            PendingAction::SetInheritancePolicy(InheritancePolicy {
+                inactivity_period,
+                claim_delay,
+            }),
+        )
+    }
+    
+    /// Charge the performance fee on a sleeve's NAV gain above its high-water mark
+    ///
+    /// Permissionless, at most once per crystallization period. The fee is taken by
+    /// minting sleeve shares to the treasury, diluting members by the fee value.
+    pub fn crystallize_performance_fee(env: Env, profile: RiskProfile) -> FeeAccrual {
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let current_time = env.ledger().timestamp();
+        
+        let mut sleeve = Self::get_sleeve_state(&env, &profile);
+        Self::accrue_management_fee(&env, &config, &mut sleeve);
+        
+        let mut accrual = Self::get_fee_accrual(&env, &profile);
+        if accrual.last_crystallization > 0
+            && current_time < accrual.last_crystallization + CRYSTALLIZATION_PERIOD
+        {
+            panic!("Crystallization period not elapsed");
+        }
+        
+        let nav = Self::nav_per_share(&sleeve);
+        let mut fee_value = 0i128;
+        
+        if sleeve.total_shares > 0 && nav > sleeve.high_water_mark {
+            let gain = ((nav - sleeve.high_water_mark) * sleeve.total_shares) / NAV_SCALE;
+            fee_value = (gain * config.performance_fee as i128) / 10000;
+            
+            let fee_shares = Self::dilution_shares(&sleeve, fee_value);
+            sleeve.total_shares += fee_shares;
+            sleeve.high_water_mark = Self::nav_per_share(&sleeve);
+            
+            accrual.fee_shares += fee_shares;
+            accrual.performance_fees += fee_value;
+        }
+        
+        env.storage().persistent().set(&(SLEEVE, profile), &sleeve);
+        accrual.last_crystallization = current_time;
+        env.storage().persistent().set(&(FEE_ACCRUAL, profile), &accrual);
+        
+        log!(
+            &env,
+            "Performance fee crystallized: profile={:?}, fee_value={}, high_water_mark={}",
+            profile,
+            fee_value,
+            sleeve.high_water_mark
+        );
+        
+        accrual
+    }
+    
+    /// Realize the management fee accrued by every sleeve up to now (permissionless)
+    pub fn collect_fees(env: Env) -> Vec<FeeAccrual> {
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        Self::accrue_all_management_fees(&env, &config);
+        Self::get_fee_accruals(env)
+    }
+    
+    /// Queue a new annual management fee, capped at 2% (admin only)
+    pub fn set_management_fee(env: Env, caller: Address, management_fee: u32) -> u64 {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_management_fee"), management_fee),
+        );
+        
+        let mut config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        config.management_fee = management_fee;
+        Self::validate_config(&config);
+        
+        timelock::queue(&env, PendingAction::SetManagementFee(management_fee))
+    }
+    
+    /// Get the fee shares and fees accrued to the treasury by every sleeve
+    pub fn get_fee_accruals(env: Env) -> Vec<FeeAccrual> {
+        let mut accruals = Vec::new(&env);
+        for profile in [RiskProfile::Conservative, RiskProfile::Moderate, RiskProfile::Aggressive] {
+            accruals.push_back(Self::get_fee_accrual(&env, &profile));
+        }
+        accruals
+    }
+    
+    /// Redeem fee shares of a sleeve and pay their KALE value to the treasury address
+    /// (treasury only)
+    pub fn redeem_fee_shares(env: Env, profile: RiskProfile, shares: i128) -> i128 {
+        let treasury = Self::get_treasury(env.clone());
+        treasury.require_auth();
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let mut sleeve = Self::get_sleeve_state(&env, &profile);
+        Self::accrue_management_fee(&env, &config, &mut sleeve);
+        
+        let mut accrual = Self::get_fee_accrual(&env, &profile);
+        if shares <= 0 || shares > accrual.fee_shares {
+            panic!("Insufficient fee shares");
+        }
+        
+        let value = Self::value_of_shares(&sleeve, shares);
+        sleeve.total_shares -= shares;
+        sleeve.total_value -= value;
+        env.storage().persistent().set(&(SLEEVE, profile), &sleeve);
+        
+        accrual.fee_shares -= shares;
+        env.storage().persistent().set(&(FEE_ACCRUAL, profile), &accrual);
+        
+        // The value leaves member funds and is paid straight to the treasury address
+        let mut ledger = Self::get_treasury_ledger(env.clone());
+        ledger.performance_fees += value;
+        ledger.withdrawn += value;
+        env.storage().instance().set(&TREASURY_LEDGER, &ledger);
+        Self::add_total_locked(&env, -value);
+        
+        let token_client = TokenClient::new(&env, &config.kale_token);
+        token_client.transfer(&env.current_contract_address(), &treasury, &value);
+        Self::check_reserves(&env, &config);
+        
+        log!(
+            &env,
+            "Fee shares redeemed: profile={:?}, shares={}, value={}, treasury={}",
+            profile,
+            shares,
+            value,
+            treasury
+        );
+        
+        value
+    }
+    
+    /// Pay KALE out of the treasury balance (admin only)
+    pub fn withdraw_treasury(env: Env, caller: Address, to: Address, amount: i128) {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "withdraw_treasury"), to.clone(), amount),
+        );
+        
+        if amount <= 0 || amount > Self::get_treasury_balance(env.clone()) {
+            panic!("Insufficient treasury balance");
+        }
+        
+        let mut ledger = Self::get_treasury_ledger(env.clone());
+        ledger.withdrawn += amount;
+        env.storage().instance().set(&TREASURY_LEDGER, &ledger);
+        
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let token_client = TokenClient::new(&env, &config.kale_token);
+        token_client.transfer(&env.current_contract_address(), &to, &amount);
+        Self::check_reserves(&env, &config);
+        
+        log!(&env, "Treasury withdrawal: to={}, amount={}", to, amount);
+    }
+    
+    /// Pay a deposit asset's collected spread out of the treasury (admin only)
+    pub fn withdraw_asset_treasury(env: Env, caller: Address, asset: Symbol, to: Address, amount: i128) {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "withdraw_asset_treasury"), asset.clone(), to.clone(), amount),
+        );
+        
+        let mut deposit_asset = Self::get_deposit_asset(env.clone(), asset.clone());
+        if amount <= 0 || amount > deposit_asset.treasury {
+            panic!("Insufficient treasury balance");
+        }
+        deposit_asset.treasury -= amount;
+        env.storage().persistent().set(&(DEPOSIT_ASSET, asset.clone()), &deposit_asset);
+        
+        let token_client = TokenClient::new(&env, &deposit_asset.token);
+        token_client.transfer(&env.current_contract_address(), &to, &amount);
+        
+        log!(&env, "Asset treasury withdrawal: asset={}, to={}, amount={}", asset, to, amount);
+    }
+    
+    /// Get the treasury's record of collected fees and penalties
+    pub fn get_treasury_ledger(env: Env) -> TreasuryLedger {
+        env.storage().instance().get(&TREASURY_LEDGER).unwrap_or(TreasuryLedger {
+            withdrawal_fees: 0,
+            early_withdrawal_penalties: 0,
+            performance_fees: 0,
+            switching_fees: 0,
+            withdrawn: 0,
+        })
+    }
+    
+    /// Get the KALE the treasury can still withdraw
+    pub fn get_treasury_balance(env: Env) -> i128 {
+        let ledger = Self::get_treasury_ledger(env);
+        ledger.withdrawal_fees + ledger.early_withdrawal_penalties + ledger.performance_fees
+            + ledger.switching_fees
+            - ledger.withdrawn
+    }
+    
+    /// Whether early withdrawal penalties are left in the sleeve for remaining members
+    pub fn is_penalty_redistributed(env: Env) -> bool {
+        env.storage().instance().get(&PENALTY_REDISTRIBUTION).unwrap_or(false)
+    }
+    
+    /// Queue redistributing early withdrawal penalties to remaining members instead of
+    /// the treasury (admin only)
+    ///
+    /// Returns the id to pass to `execute_change` once the timelock delay has passed.
+    pub fn set_penalty_redistribution(env: Env, caller: Address, enabled: bool) -> u64 {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_penalty_redistribution"), enabled),
+        );
+        
+        timelock::queue(&env, PendingAction::SetPenaltyRedistribution(enabled))
+    }
+    
+    /// Get the treasury address receiving fee shares
+    pub fn get_treasury(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&TREASURY)
+            .unwrap_or_else(|| env.storage().instance().get(&ADMIN).unwrap())
+    }
+    
+    /// Queue a new treasury address receiving fee shares (admin only)
+    ///
+    /// Returns the id to pass to `execute_change` once the timelock delay has passed.
+    pub fn set_treasury(env: Env, caller: Address, treasury: Address) -> u64 {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_treasury"), treasury.clone()));
+        
+        timelock::queue(&env, PendingAction::SetTreasury(treasury))
+    }
+    
+    /// Move a member's whole position to another risk profile's sleeve at NAV
+    ///
+    /// Shares in the current sleeve are redeemed at its NAV and the proceeds, less
+    /// the switching fee, buy shares in the new sleeve. The switching fee goes to
+    /// the treasury. Underlying holdings are migrated in kind by the
+    /// keeper on the next rebalance of both sleeves.
+    pub fn change_risk_profile(env: Env, user: Address, new_profile: RiskProfile) -> ProfileChange {
+        user.require_auth();
+        Self::require_not_frozen(&env, &user);
+        
+        let mut user_account = Self::get_user_account(&env, &user);
+        let old_profile = user_account.risk_profile;
+        if old_profile == new_profile {
+            panic!("Already in risk profile");
+        }
+        if Self::get_queued_shares(env.clone(), user.clone()) > 0 {
+            panic!("Withdrawal pending");
+        }
+        if Self::unvested_shares(&env, &user) > 0 {
+            panic!("Unvested employer contributions");
+        }
+        
+        let policy = Self::get_profile_switch_policy(env.clone());
+        let current_time = env.ledger().timestamp();
+        let mut history = Self::get_profile_history(env.clone(), user.clone());
+        if let Some(last_change) = history.last() {
+            if current_time < last_change.timestamp + policy.cooldown {
+                panic!("Profile change cooldown active");
+            }
+        }
+        
+        // Redeem the whole position from the old sleeve
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let mut old_sleeve = Self::get_sleeve_state(&env, &old_profile);
+        Self::accrue_management_fee(&env, &config, &mut old_sleeve);
+        let value_moved = Self::value_of_shares(&old_sleeve, user_account.shares);
+        let fee = (value_moved * policy.switching_fee as i128) / 10000;
+        
+        // Switching in counts against the new sleeve's capacity like a deposit would
+        if value_moved - fee > Self::get_deposit_headroom(env.clone(), user.clone(), new_profile).profile {
+            panic!("Deposit cap exceeded");
+        }
+        
+        let shares_burned = user_account.shares;
+        old_sleeve.total_shares -= shares_burned;
+        old_sleeve.total_value -= value_moved;
+        env.storage().persistent().set(&(SLEEVE, old_profile), &old_sleeve);
+        
+        if fee > 0 {
+            let mut ledger = Self::get_treasury_ledger(env.clone());
+            ledger.switching_fees += fee;
+            env.storage().instance().set(&TREASURY_LEDGER, &ledger);
+            Self::add_total_locked(&env, -fee);
+        }
+        
+        // Buy into the new sleeve at its NAV
+        let mut new_sleeve = Self::get_sleeve_state(&env, &new_profile);
+        Self::accrue_management_fee(&env, &config, &mut new_sleeve);
+        let shares_issued = Self::shares_for_value(&new_sleeve, value_moved - fee);
+        new_sleeve.total_shares += shares_issued;
+        new_sleeve.total_value += value_moved - fee;
+        env.storage().persistent().set(&(SLEEVE, new_profile), &new_sleeve);
+        
+        // Tranches keep their unlock times, re-denominated in the new sleeve's shares
+        Self::rescale_tranches(&env, &user, &user_account, shares_issued);
+        
+        user_account.risk_profile = new_profile;
+        user_account.shares = shares_issued;
+        user_account.balance = Self::value_of_shares(&new_sleeve, shares_issued);
+        env.storage().persistent().set(&user, &user_account);
+        Self::checkpoint_voting_power(&env, &user);
+        Self::record_activity(&env, &user, ActivityKind::ProfileChange, value_moved, shares_issued);
+        if fee > 0 {
+            Self::record_activity(&env, &user, ActivityKind::Fee, fee, 0);
+        }
+        
+        let change = ProfileChange {
+            from_profile: old_profile,
+            to_profile: new_profile,
+            value_moved,
+            fee,
+            shares_burned,
+            shares_issued,
+            timestamp: current_time,
+        };
+        history.push_back(change.clone());
+        env.storage().persistent().set(&(PROFILE_HISTORY, user.clone()), &history);
+        Self::mark_active(&env, &user);
+        
+        log!(
+            &env,
+            "Risk profile changed: user={}, from={:?}, to={:?}, value={}, fee={}",
+            user,
+            old_profile,
+            new_profile,
+            value_moved,
+            fee
+        );
+        
+        change
+    }
+    
+    /// Get members in joining order, `limit` at a time from index `start`
+    pub fn get_members(env: Env, start: u32, limit: u32) -> Vec<Address> {
+        let count = Self::get_member_count(env.clone());
+        let end = start.saturating_add(limit).min(count);
+        let mut members = Vec::new(&env);
+        for index in start..end {
+            members.push_back(env.storage().persistent().get(&(MEMBER, index)).unwrap());
+        }
+        members
+    }
+    
+    /// Get the number of members who have ever deposited
+    pub fn get_member_count(env: Env) -> u32 {
+        env.storage().instance().get(&MEMBER_COUNT).unwrap_or(0)
+    }
+    
+    /// Get fund-wide statistics
+    pub fn get_fund_stats(env: Env) -> FundStats {
+        let mut totals = Self::get_member_totals(&env);
+        let current_time = env.ledger().timestamp();
+        let today = current_time / UNLOCK_BUCKET_SIZE;
+        Self::expire_unlock_buckets(&env, &mut totals, today, false);
+        
+        // Locks ending after today count in full; today's may partly have ended already
+        let now = current_time as i128;
+        let ending_today = Self::get_unlock_bucket(&env, today);
+        let later_members = (totals.locked_members - ending_today.members) as i128;
+        let remaining = totals.unlock_time_sum - ending_today.unlock_time_sum - now * later_members
+            + (ending_today.unlock_time_sum - now * ending_today.members as i128).max(0);
+        let average_lock_remaining = if totals.invested_members == 0 {
+            0
+        } else {
+            (remaining / totals.invested_members as i128) as u64
+        };
+        
+        let ledger = Self::get_treasury_ledger(env.clone());
+        let mut total_fees_collected = ledger.withdrawal_fees + ledger.switching_fees;
+        let mut tvl = [0i128; 3];
+        for (i, profile) in [RiskProfile::Conservative, RiskProfile::Moderate, RiskProfile::Aggressive]
+            .iter()
+            .enumerate()
+        {
+            tvl[i] = Self::get_sleeve_state(&env, profile).total_value;
+            let accrual = Self::get_fee_accrual(&env, profile);
+            total_fees_collected += accrual.performance_fees + accrual.management_fees;
+        }
+        
+        FundStats {
+            member_count: Self::get_member_count(env.clone()),
+            invested_members: totals.invested_members,
+            tvl_conservative: tvl[0],
+            tvl_moderate: tvl[1],
+            tvl_aggressive: tvl[2],
+            total_value_locked: tvl[0] + tvl[1] + tvl[2],
+            average_lock_remaining,
+            total_fees_collected,
+        }
+    }
+    
+    /// Get a member's statement for activity between `from` and `to` (inclusive)
+    ///
+    /// Returns up to `limit` entries starting at the `start`-th matching entry; the
+    /// totals cover every matching entry. Only the latest entries are kept on-chain.
+    pub fn get_statement(env: Env, user: Address, from: u64, to: u64, start: u32, limit: u32) -> Statement {
+        let log: Vec<ActivityEntry> = env
+            .storage()
+            .persistent()
+            .get(&(ACTIVITY, user.clone()))
+            .unwrap_or(Vec::new(&env));
+        
+        let mut statement = Statement {
+            user: user.clone(),
+            from,
+            to,
+            entries: Vec::new(&env),
+            total_entries: 0,
+            next_start: None,
+            deposits: 0,
+            withdrawals: 0,
+            fees: 0,
+            penalties: 0,
+            rewards: 0,
+            balance: Self::get_account(env.clone(), user).balance,
+        };
+        
+        for entry in log.iter() {
+            if entry.timestamp < from || entry.timestamp > to {
+                continue;
+            }
+            match entry.kind {
+                ActivityKind::Deposit => statement.deposits += entry.amount,
+                ActivityKind::Withdrawal => statement.withdrawals += entry.amount,
+                ActivityKind::Fee => statement.fees += entry.amount,
+                ActivityKind::Penalty => statement.penalties += entry.amount,
+                ActivityKind::Reward => statement.rewards += entry.amount,
+                ActivityKind::ProfileChange | ActivityKind::Forfeiture => {}
+            }
+            
+            let index = statement.total_entries;
+            statement.total_entries += 1;
+            if index < start {
+                continue;
+            }
+            if statement.entries.len() < limit {
+                statement.entries.push_back(entry);
+            } else if statement.next_start.is_none() {
+                statement.next_start = Some(index);
+            }
+        }
+        
+        statement
+    }
+    
+    /// Get a member's history of risk profile changes
+    pub fn get_profile_history(env: Env, user: Address) -> Vec<ProfileChange> {
+        env.storage()
+            .persistent()
+            .get(&(PROFILE_HISTORY, user))
+            .unwrap_or(Vec::new(&env))
+    }
+    
+    /// Get the cooldown and fee applied to risk profile changes
+    pub fn get_profile_switch_policy(env: Env) -> ProfileSwitchPolicy {
+        env.storage().instance().get(&SWITCH_POLICY).unwrap_or(ProfileSwitchPolicy {
+            cooldown: DEFAULT_SWITCH_COOLDOWN,
+            switching_fee: 0,
+        })
+    }
+    
+    /// Queue a new cooldown and fee for risk profile changes (admin only)
+    ///
+    /// Returns the id to pass to `execute_change` once the timelock delay has passed.
+    pub fn set_profile_switch_policy(env: Env, caller: Address, cooldown: u64, switching_fee: u32) -> u64 {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_profile_switch_policy"), cooldown, switching_fee),
+        );
+        
+        if switching_fee > MAX_SWITCHING_FEE {
+            panic!("Switching fee above cap");
+        }
+        
+        timelock::queue(
+            &env,
+            PendingAction::SetProfileSwitchPolicy(ProfileSwitchPolicy {
+                cooldown,
+                switching_fee,
+            }),
+        )
+    }
+    
+    /// Get user account information, with the balance marked to the sleeve's current NAV
+    /// net of the management fee accrued so far
+    pub fn get_account(env: Env, user: Address) -> UserAccount {
+        let mut user_account = Self::get_user_account(&env, &user);
+        let sleeve = Self::accrued_sleeve(&env, &user_account.risk_profile);
+        user_account.balance = Self::value_of_shares(&sleeve, user_account.shares);
+        user_account
+    }
+    
+    /// Get a member's deposit tranches with their unlock times
+    pub fn get_tranches(env: Env, user: Address) -> Vec<DepositTranche> {
+        let user_account = Self::get_user_account(&env, &user);
+        Self::load_tranches(&env, &user, &user_account)
+    }
+    
+    /// Get the sleeve (sub-portfolio) for a risk profile
+    pub fn get_sleeve(env: Env, profile: RiskProfile) -> Sleeve {
+        Self::get_sleeve_state(&env, &profile)
+    }
+    
+    /// Get the NAV per share of a sleeve (scaled by 1e7), net of accrued management fees
+    pub fn get_nav(env: Env, profile: RiskProfile) -> i128 {
+        let sleeve = Self::accrued_sleeve(&env, &profile);
+        Self::nav_per_share(&sleeve)
+    }
+    
+    /// Record the marked-to-market value of a sleeve's holdings (admin only)
+    ///
+    /// The admin reports the KALE-denominated value of the sleeve as rebalanced by
+    /// the rebalancer against the profile's target allocation; shares are unchanged
+    /// so members' balances move with the sleeve's NAV.
+    pub fn update_sleeve_value(env: Env, caller: Address, profile: RiskProfile, total_value: i128) {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "update_sleeve_value"), profile, total_value),
+        );
+        
+        if total_value < 0 {
+            panic!("Invalid sleeve value");
+        }
+        
+        // Fees up to now are charged on the previous valuation
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let mut sleeve = Self::get_sleeve_state(&env, &profile);
+        Self::accrue_management_fee(&env, &config, &mut sleeve);
+        if sleeve.total_shares == 0 && total_value > 0 {
+            panic!("Sleeve has no shares outstanding");
+        }
+        
+        sleeve.total_value = total_value;
+        sleeve.last_valuation = env.ledger().timestamp();
+        env.storage().persistent().set(&(SLEEVE, profile), &sleeve);
+        
+        log!(
+            &env,
+            "Sleeve revalued: profile={:?}, total_value={}, nav={}",
+            profile,
+            total_value,
+            Self::nav_per_share(&sleeve)
+        );
+    }
+    
+    /// Get fund configuration
+    pub fn get_config(env: Env) -> FundConfig {
+        env.storage().instance().get(&FUND_CONFIG).unwrap()
+    }
+    
+    /// Get total value locked in the fund
+    pub fn get_total_locked(env: Env) -> i128 {
+        env.storage().instance().get(&TOTAL_LOCKED).unwrap_or(0)
+    }
+    
+    /// Compare the fund's ledgers with its KALE token balance
+    pub fn reconcile(env: Env) -> Reconciliation {
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let token_balance = TokenClient::new(&env, &config.kale_token).balance(&env.current_contract_address());
+        
+        let member_funds = Self::get_total_locked(env.clone());
+        let treasury = Self::get_treasury_balance(env.clone());
+        let reward_reserve = Self::reward_reserve(&env);
+        let pending_claims = Self::get_claims_reserved(env.clone());
+        let expected_balance = member_funds + treasury + reward_reserve + pending_claims;
+        
+        Reconciliation {
+            member_funds,
+            treasury,
+            reward_reserve,
+            pending_claims,
+            expected_balance,
+            token_balance,
+            surplus: token_balance - expected_balance,
+        }
+    }
+    
+    /// Queue a switch to a new KALE token contract (admin only)
+    ///
+    /// Only executes while the fund owes nothing in the old token, as measured by
+    /// `reconcile`, and no sleeve has shares outstanding.
+    pub fn migrate_kale_token(env: Env, caller: Address, new_token: Address) -> u64 {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "migrate_kale_token"), new_token.clone()),
+        );
+        
+        timelock::queue(&env, PendingAction::MigrateKaleToken(new_token))
+    }
+    
+    /// Queue a fund configuration update behind the timelock (admin only)
+    ///
+    /// Returns the id to pass to `execute_change` once the timelock delay has passed.
+    pub fn update_config(
+        env: Env,
+        caller: Address,
+        min_deposit: Option<i128>,
+        max_deposit: Option<i128>,
+        withdrawal_fee: Option<u32>,
+        performance_fee: Option<u32>,
+        early_withdrawal_penalty: Option<u32>,
+        lock_period: Option<u64>,
+        referral_bonus: Option<u32>,
+    ) -> u64 {
+        Self::require_admin(
+            &env,
+            &caller,
+            (
+                Symbol::new(&env, "update_config"),
+                min_deposit,
+                max_deposit,
+                withdrawal_fee,
+                performance_fee,
+                early_withdrawal_penalty,
+                lock_period,
+                referral_bonus,
+            ),
+        );
+        
+        let update = ConfigUpdate {
+            min_deposit,
+            max_deposit,
+            withdrawal_fee,
+            performance_fee,
+            early_withdrawal_penalty,
+            lock_period,
+            referral_bonus,
+        };
+        
+        // Reject invalid updates now rather than when they are executed
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        Self::validate_config(&Self::apply_config_update(config, &update));
+        
+        timelock::queue(&env, PendingAction::UpdateConfig(update))
+    }
+    
+    /// Execute a queued change once its timelock has expired (permissionless)
+    pub fn execute_change(env: Env, id: u64) {
+        let action: PendingAction = timelock::take_ready(&env, id);
+        match action {
+            PendingAction::UpdateConfig(update) => {
+                let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+                let config = Self::apply_config_update(config, &update);
+                Self::validate_config(&config);
+                env.storage().instance().set(&FUND_CONFIG, &config);
+            }
+            PendingAction::UpdateFees(update) => {
+                let mut config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+                Self::accrue_all_management_fees(&env, &config);
+                config = Self::apply_fee_update(config, &update);
+                Self::validate_config(&config);
+                env.storage().instance().set(&FUND_CONFIG, &config);
+            }
+            PendingAction::SetGovernanceConfig(governance_config) => {
+                env.storage().instance().set(&GOVERNANCE_CONFIG, &governance_config);
+            }
+            PendingAction::SetDepositLimits(limits) => {
+                env.storage().instance().set(&DEPOSIT_LIMITS, &limits);
+            }
+            PendingAction::SetManagementFee(management_fee) => {
+                // Time elapsed so far is charged at the old rate
+                let mut config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+                Self::accrue_all_management_fees(&env, &config);
+                config.management_fee = management_fee;
+                Self::validate_config(&config);
+                env.storage().instance().set(&FUND_CONFIG, &config);
+            }
+            PendingAction::SetProfileSwitchPolicy(policy) => {
+                env.storage().instance().set(&SWITCH_POLICY, &policy);
+            }
+            PendingAction::SetInheritancePolicy(policy) => {
+                env.storage().instance().set(&INHERIT_POLICY, &policy);
+            }
+            PendingAction::SetWithdrawalNotice(notice) => {
+                env.storage().instance().set(&WITHDRAWAL_NOTICE, &notice);
+            }
+            PendingAction::RegisterDepositAsset(asset, token, spread) => {
+                Self::store_deposit_asset(&env, &asset, &token, spread);
+            }
+            PendingAction::SetReferralTiers(tier_bonuses) => {
+                env.storage().instance().set(&REFERRAL_TIERS, &tier_bonuses);
+            }
+            PendingAction::SetPenaltyRedistribution(enabled) => {
+                env.storage().instance().set(&PENALTY_REDISTRIBUTION, &enabled);
+            }
+            PendingAction::MigrateKaleToken(new_token) => {
+                if Self::reconcile(env.clone()).expected_balance != 0 {
+                    panic!("Fund holds balances in current token");
+                }
+                for profile in [RiskProfile::Conservative, RiskProfile::Moderate, RiskProfile::Aggressive] {
+                    if Self::get_sleeve_state(&env, &profile).total_shares != 0 {
+                        panic!("Sleeve shares outstanding");
+                    }
+                }
+                let mut config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+                config.kale_token = new_token;
+                env.storage().instance().set(&FUND_CONFIG, &config);
+            }
+            PendingAction::SetOracle(oracle) => {
+                env.storage().instance().set(&ORACLE, &oracle);
+            }
+            PendingAction::SetTreasury(treasury) => {
+                env.storage().instance().set(&TREASURY, &treasury);
+            }
+            PendingAction::SetRiskManager(risk_manager) => {
+                env.storage().instance().set(&RISK_MANAGER, &risk_manager);
+            }
+            PendingAction::SetHardshipLimit(annual_limit) => {
+                env.storage().instance().set(&HARDSHIP_LIMIT, &annual_limit);
+            }
+            PendingAction::SetTimelockDelay(delay) => {
+                timelock::set_delay(&env, delay);
+            }
+        }
+        
+        log!(&env, "Queued change executed: id={}", id);
+    }
+    
+    /// Cancel a queued change (guardian only)
+    pub fn cancel_change(env: Env, caller: Address, id: u64) {
+        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
+        timelock::cancel(&env, &admin, &caller, id);
+    }
+    
+    /// Queue a new timelock delay (admin only)
+    pub fn set_timelock_delay(env: Env, caller: Address, delay: u64) -> u64 {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_timelock_delay"), delay));
+        
+        timelock::validate_delay(delay);
+        
+        timelock::queue(&env, PendingAction::SetTimelockDelay(delay))
+    }
+    
+    /// Set the guardian allowed to cancel queued changes (admin only)
+    pub fn set_guardian(env: Env, caller: Address, guardian: Address) {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_guardian"), guardian.clone()));
+        
+        timelock::set_guardian(&env, &guardian);
+    }
+    
+    /// Open a member vote on a fee or allocation change
+    ///
+    /// Voting power is each member's balance as recorded at their last deposit,
+    /// withdrawal or profile change, taken at the ledger before the proposal.
+    pub fn propose(env: Env, proposer: Address, action: ProposalAction) -> u64 {
+        proposer.require_auth();
+        
+        let governance = Self::get_governance_config(env.clone());
+        let snapshot_ledger = env.ledger().sequence().saturating_sub(1);
+        let proposer_weight = Self::voting_power_at(&env, &proposer, snapshot_ledger);
+        if proposer_weight == 0 || proposer_weight < governance.proposal_threshold {
+            panic!("Insufficient voting power");
+        }
+        
+        // Reject proposals that could never be executed
+        match &action {
+            ProposalAction::UpdateFees(update) => {
+                let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+                Self::validate_config(&Self::apply_fee_update(config, update));
+            }
+            ProposalAction::UpdateAllocation(proposal) => {
+                let allocation = &proposal.allocation;
+                let total = allocation.kale_percentage + allocation.btc_percentage
+                    + allocation.usdc_percentage + allocation.xlm_percentage;
+                if total != 10000 {
+                    panic!("Allocation percentages must sum to 100%");
+                }
+                if !env.storage().instance().has(&RISK_MANAGER) {
+                    panic!("Risk manager not set");
+                }
+            }
+        }
+        
+        let id: u64 = env.storage().instance().get(&NEXT_PROPOSAL_ID).unwrap_or(1);
+        env.storage().instance().set(&NEXT_PROPOSAL_ID, &(id + 1));
+        
+        let proposal = Proposal {
+            id,
+            proposer: proposer.clone(),
+            action,
+            snapshot_ledger,
+            total_weight: Self::total_voting_power_at(&env, snapshot_ledger),
+            votes_for: 0,
+            votes_against: 0,
+            end_time: env.ledger().timestamp() + governance.voting_period,
+            status: ProposalStatus::Active,
+            queued_change: 0,
+        };
+        env.storage().persistent().set(&(PROPOSAL, id), &proposal);
+        
+        log!(&env, "Proposal created: id={}, proposer={}, end_time={}", id, proposer, proposal.end_time);
+        
+        id
+    }
+    
+    /// Vote on an active proposal with the voter's invested principal at its snapshot
+    pub fn vote(env: Env, voter: Address, proposal_id: u64, support: bool) -> i128 {
+        voter.require_auth();
+        
+        let mut proposal = Self::get_proposal(env.clone(), proposal_id);
+        if proposal.status != ProposalStatus::Active || env.ledger().timestamp() >= proposal.end_time {
+            panic!("Voting closed");
+        }
+        
+        let receipt_key = (VOTE_RECEIPT, proposal_id, voter.clone());
+        if env.storage().persistent().has(&receipt_key) {
+            panic!("Already voted");
+        }
+        
+        let weight = Self::voting_power_at(&env, &voter, proposal.snapshot_ledger);
+        if weight == 0 {
+            panic!("No voting power");
+        }
+        
+        if support {
+            proposal.votes_for += weight;
+        } else {
+            proposal.votes_against += weight;
+        }
+        env.storage().persistent().set(&receipt_key, &support);
+        env.storage().persistent().set(&(PROPOSAL, proposal_id), &proposal);
+        
+        log!(&env, "Vote cast: proposal={}, voter={}, support={}, weight={}", proposal_id, voter, support, weight);
+        
+        weight
+    }
+    
+    /// Tally a proposal after voting ends and queue it in the timelock if it passed (permissionless)
+    ///
+    /// Fee changes are queued in this contract's timelock; allocation changes are
+    /// queued in the risk manager's timelock, where this contract is the governor.
+    pub fn execute_proposal(env: Env, proposal_id: u64) -> ProposalStatus {
+        let mut proposal = Self::get_proposal(env.clone(), proposal_id);
+        if proposal.status != ProposalStatus::Active {
+            panic!("Proposal not active");
+        }
+        if env.ledger().timestamp() < proposal.end_time {
+            panic!("Voting not ended");
+        }
+        
+        let governance = Self::get_governance_config(env.clone());
+        let votes_cast = proposal.votes_for + proposal.votes_against;
+        let quorum_reached =
+            votes_cast * 10000 >= proposal.total_weight * governance.quorum as i128;
+        let approved = proposal.votes_for * 10000 > votes_cast * governance.approval_threshold as i128;
+        
+        if !quorum_reached || !approved {
+            proposal.status = ProposalStatus::Defeated;
+        } else {
+            proposal.queued_change = match proposal.action.clone() {
+                ProposalAction::UpdateFees(update) => {
+                    timelock::queue(&env, PendingAction::UpdateFees(update))
+                }
+                ProposalAction::UpdateAllocation(change) => {
+                    let risk_manager: Address = env.storage().instance().get(&RISK_MANAGER).unwrap();
+                    RiskManagerClient::new(&env, &risk_manager).update_allocation(
+                        &env.current_contract_address(),
+                        &change.profile,
+                        &change.allocation,
+                    )
+                }
+            };
+            proposal.status = ProposalStatus::Queued;
+        }
+        env.storage().persistent().set(&(PROPOSAL, proposal_id), &proposal);
+        
+        log!(
+            &env,
+            "Proposal tallied: id={}, for={}, against={}, status={:?}",
+            proposal_id,
+            proposal.votes_for,
+            proposal.votes_against,
+            proposal.status
+        );
+        
+        proposal.status
+    }
+    
+    /// Get a proposal
+    pub fn get_proposal(env: Env, proposal_id: u64) -> Proposal {
+        env.storage()
+            .persistent()
+            .get(&(PROPOSAL, proposal_id))
+            .unwrap_or_else(|| panic!("Unknown proposal"))
+    }
+    
+    /// Get a member's voting power at a ledger
+    ///
+    /// Voting power is the KALE principal still invested, summed over the member's
+    /// tranches, so it does not move with sleeve NAV.
+    pub fn get_voting_power(env: Env, user: Address, ledger: u32) -> i128 {
+        Self::voting_power_at(&env, &user, ledger)
+    }
+    
+    /// Get the voting period, quorum and thresholds for member proposals
+    pub fn get_governance_config(env: Env) -> GovernanceConfig {
+        env.storage().instance().get(&GOVERNANCE_CONFIG).unwrap_or(GovernanceConfig {
+            voting_period: DEFAULT_VOTING_PERIOD,
+            quorum: DEFAULT_QUORUM,
+            approval_threshold: DEFAULT_APPROVAL_THRESHOLD,
+            proposal_threshold: 0,
+        })
+    }
+    
+    /// Queue new voting period, quorum and thresholds for member proposals (admin only)
+    pub fn set_governance_config(env: Env, caller: Address, governance_config: GovernanceConfig) -> u64 {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_governance_config"), governance_config.clone()),
+        );
+        
+        if governance_config.voting_period == 0
+            || governance_config.quorum > 10000
+            || governance_config.approval_threshold > 10000
+        {
+            panic!("Invalid governance config");
+        }
+        
+        timelock::queue(&env, PendingAction::SetGovernanceConfig(governance_config))
+    }
+    
+    /// Queue new contribution caps and capacity limits (admin only)
+    pub fn set_deposit_limits(env: Env, caller: Address, limits: DepositLimits) -> u64 {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_deposit_limits"), limits.clone()));
+        
+        if limits.member_lifetime_cap < 0
+            || limits.member_annual_cap < 0
+            || limits.fund_tvl_cap < 0
+            || limits.conservative_capacity < 0
+            || limits.moderate_capacity < 0
+            || limits.aggressive_capacity < 0
+        {
+            panic!("Invalid deposit limits");
+        }
+        
+        timelock::queue(&env, PendingAction::SetDepositLimits(limits))
+    }
+    
+    /// Get the contribution caps and capacity limits
+    pub fn get_deposit_limits(env: Env) -> DepositLimits {
+        env.storage().instance().get(&DEPOSIT_LIMITS).unwrap_or(DepositLimits {
+            member_lifetime_cap: 0,
+            member_annual_cap: 0,
+            fund_tvl_cap: 0,
+            conservative_capacity: 0,
+            moderate_capacity: 0,
+            aggressive_capacity: 0,
+        })
+    }
+    
+    /// Get how much more a member can deposit into a risk profile's sleeve
+    pub fn get_deposit_headroom(env: Env, user: Address, risk_profile: RiskProfile) -> DepositHeadroom {
+        let limits = Self::get_deposit_limits(env.clone());
+        let user_account = Self::get_user_account(&env, &user);
+        
+        let year = env.ledger().timestamp() / SECONDS_PER_YEAR;
+        let annual = Self::get_annual_contributions(env.clone(), user);
+        let contributed_this_year = if annual.year == year { annual.total } else { 0 };
+        
+        let mut fund_value = 0;
+        for profile in [RiskProfile::Conservative, RiskProfile::Moderate, RiskProfile::Aggressive] {
+            fund_value += Self::get_sleeve_state(&env, &profile).total_value;
+        }
+        let (profile_capacity, profile_value) = (
+            match risk_profile {
+                RiskProfile::Conservative => limits.conservative_capacity,
+                RiskProfile::Moderate => limits.moderate_capacity,
+                RiskProfile::Aggressive => limits.aggressive_capacity,
+            },
+            Self::get_sleeve_state(&env, &risk_profile).total_value,
+        );
+        
+        let member_lifetime = Self::cap_headroom(limits.member_lifetime_cap, user_account.total_deposits);
+        let member_annual = Self::cap_headroom(limits.member_annual_cap, contributed_this_year);
+        let fund = Self::cap_headroom(limits.fund_tvl_cap, fund_value);
+        let profile = Self::cap_headroom(profile_capacity, profile_value);
+        
+        DepositHeadroom {
+            member_lifetime,
+            member_annual,
+            fund,
+            profile,
+            available: member_lifetime.min(member_annual).min(fund).min(profile),
+        }
+    }
+    
+    /// Get a member's contributions in their latest contribution year
+    pub fn get_annual_contributions(env: Env, user: Address) -> AnnualContributions {
+        env.storage()
+            .persistent()
+            .get(&(ANNUAL_CONTRIBUTIONS, user))
+            .unwrap_or(AnnualContributions { year: 0, total: 0 })
+    }
+    
+    /// Require a valid attestation for deposits, or lift the requirement (admin only)
+    pub fn set_compliance_mode(env: Env, caller: Address, enabled: bool) {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_compliance_mode"), enabled));
+        
+        env.storage().instance().set(&COMPLIANCE_MODE, &enabled);
+        
+        log!(&env, "Compliance mode set: {}", enabled);
+    }
+    
+    /// Whether deposits require a valid attestation
+    pub fn is_compliance_mode(env: Env) -> bool {
+        env.storage().instance().get(&COMPLIANCE_MODE).unwrap_or(false)
+    }
+    
+    /// Set the compliance officer who manages attesters and the denylist (admin only)
+    pub fn set_compliance_officer(env: Env, caller: Address, officer: Address) {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_compliance_officer"), officer.clone()));
+        
+        env.storage().instance().set(&COMPLIANCE_OFFICER, &officer);
+        
+        log!(&env, "Compliance officer set: {}", officer);
+    }
+    
+    /// Get the compliance officer, if any
+    pub fn get_compliance_officer(env: Env) -> Option<Address> {
+        env.storage().instance().get(&COMPLIANCE_OFFICER)
+    }
+    
+    /// Register or remove an attester account or contract (compliance officer or admin)
+    ///
+    /// Attestations from a removed attester stop counting as valid.
+    pub fn set_attester(env: Env, caller: Address, attester: Address, enabled: bool) {
+        Self::require_compliance(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_attester"), attester.clone(), enabled),
+        );
+        
+        if enabled {
+            env.storage().persistent().set(&(ATTESTER, attester.clone()), &true);
+        } else {
+            env.storage().persistent().remove(&(ATTESTER, attester.clone()));
+        }
+        
+        log!(&env, "Attester updated: attester={}, enabled={}", attester, enabled);
+    }
+    
+    /// Check whether an address is a registered attester
+    pub fn is_attester(env: Env, attester: Address) -> bool {
+        env.storage().persistent().has(&(ATTESTER, attester))
+    }
+    
+    /// Attest that a member is verified until `expires_at` (registered attesters only)
+    pub fn attest(env: Env, attester: Address, user: Address, expires_at: u64) -> Attestation {
+        if !Self::is_attester(env.clone(), attester.clone()) {
+            panic!("Unauthorized");
+        }
+        attester.require_auth();
+        
+        let current_time = env.ledger().timestamp();
+        if expires_at <= current_time {
+            panic!("Invalid expiry");
+        }
+        
+        let attestation = Attestation {
+            attester: attester.clone(),
+            issued_at: current_time,
+            expires_at,
+            revoked: false,
+        };
+        env.storage().persistent().set(&(ATTESTATION, user.clone()), &attestation);
+        
+        log!(&env, "Attestation issued: attester={}, user={}, expires_at={}", attester, user, expires_at);
+        
+        attestation
+    }
+    
+    /// Revoke a member's attestation (its attester, the compliance officer or admin)
+    pub fn revoke_attestation(env: Env, caller: Address, user: Address) {
+        let mut attestation = Self::get_attestation(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No attestation"));
+        if caller == attestation.attester {
+            caller.require_auth();
+        } else {
+            Self::require_compliance(
+                &env,
+                &caller,
+                (Symbol::new(&env, "revoke_attestation"), user.clone()),
+            );
+        }
+        
+        attestation.revoked = true;
+        env.storage().persistent().set(&(ATTESTATION, user.clone()), &attestation);
+        
+        log!(&env, "Attestation revoked: user={}, by={}", user, caller);
+    }
+    
+    /// Get a member's latest attestation
+    pub fn get_attestation(env: Env, user: Address) -> Option<Attestation> {
+        env.storage().persistent().get(&(ATTESTATION, user))
+    }
+    
+    /// Whether a member holds an unexpired, unrevoked attestation from a registered attester
+    pub fn has_valid_attestation(env: Env, user: Address) -> bool {
+        match Self::get_attestation(env.clone(), user) {
+            Some(attestation) => {
+                !attestation.revoked
+                    && env.ledger().timestamp() < attestation.expires_at
+                    && Self::is_attester(env, attestation.attester)
+            }
+            None => false,
+        }
+    }
+    
+    /// Freeze or unfreeze an account (compliance officer or admin)
+    ///
+    /// Frozen accounts cannot deposit, withdraw, switch profile or claim withdrawals.
+    pub fn set_frozen(env: Env, caller: Address, user: Address, frozen: bool) {
+        Self::require_compliance(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_frozen"), user.clone(), frozen),
+        );
+        
+        if frozen {
+            env.storage().persistent().set(&(DENYLIST, user.clone()), &true);
+        } else {
+            env.storage().persistent().remove(&(DENYLIST, user.clone()));
+        }
+        
+        log!(&env, "Account freeze updated: user={}, frozen={}", user, frozen);
+    }
+    
+    /// Check whether an account is on the denylist
+    pub fn is_frozen(env: Env, user: Address) -> bool {
+        env.storage().persistent().has(&(DENYLIST, user))
+    }
+    
+    /// Queue a new risk manager whose allocations members vote on (admin only)
+    ///
+    /// Returns the id to pass to `execute_change` once the timelock delay has passed.
+    pub fn set_risk_manager(env: Env, caller: Address, risk_manager: Address) -> u64 {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_risk_manager"), risk_manager.clone()),
+        );
+        
+        timelock::queue(&env, PendingAction::SetRiskManager(risk_manager))
+    }
+    
+    /// Get all changes waiting in the timelock
+    pub fn get_pending_changes(env: Env) -> Vec<PendingChange> {
+        let mut changes = Vec::new(&env);
+        for id in timelock::pending_ids(&env).iter() {
+            let change = timelock::get(&env, id);
+            changes.push_back(PendingChange {
+                id,
+                action: change.action,
+                queued_at: change.queued_at,
+                eta: change.eta,
+            });
+        }
+        changes
+    }
+    
+    /// Get the delay between queueing and executing a change
+    pub fn get_timelock_delay(env: Env) -> u64 {
+        timelock::delay(&env)
+    }
+    
+    /// Get the guardian allowed to cancel queued changes
+    pub fn get_guardian(env: Env) -> Address {
+        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
+        timelock::guardian(&env, &admin)
+    }
+    
+    /// Replace the multisig signer set and approval threshold (admin only)
+    pub fn set_signers(env: Env, caller: Address, signers: Vec<Address>, threshold: u32) {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_signers"), signers.clone(), threshold),
+        );
+        
+        multisig::set_signers(&env, &signers, threshold);
+    }
+    
+    /// Approve an admin action by its hash (signers only)
+    pub fn approve_action(env: Env, signer: Address, action_hash: BytesN<32>) -> u32 {
+        multisig::approve(&env, &signer, &action_hash)
+    }
+    
+    /// Withdraw a signer's approval of an action that has not been executed
+    pub fn revoke_approval(env: Env, signer: Address, action_hash: BytesN<32>) {
+        multisig::revoke(&env, &signer, &action_hash);
+    }
+    
+    /// Get the multisig signer set
+    pub fn get_signers(env: Env) -> Vec<Address> {
+        multisig::signers(&env)
+    }
+    
+    /// Get the number of signer approvals each admin action needs (0 when multisig is off)
+    pub fn get_signer_threshold(env: Env) -> u32 {
+        multisig::threshold(&env)
+    }
+    
+    /// Get the signers who approved an action
+    pub fn get_approvals(env: Env, action_hash: BytesN<32>) -> Vec<Address> {
+        multisig::approvals(&env, &action_hash)
+    }
+    
+    /// Distribute rewards to users (admin only)
+    pub fn distribute_rewards(env: Env, caller: Address, total_rewards: i128) {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "distribute_rewards"), total_rewards),
+        );
+        
+        let total_locked: i128 = env.storage().instance().get(&TOTAL_LOCKED).unwrap_or(0);
+        if total_locked == 0 {
+            return;
+        }
+        
+        // Rewards distribution logic would be implemented here
+        // This would iterate through all users and distribute proportional rewards
+        
+        log!(&env, "Rewards distributed: total={}", total_rewards);
+    }
+    
+    /// Internal helper to get user account
+    fn get_user_account(env: &Env, user: &Address) -> UserAccount {
+        env.storage().persistent().get(user).unwrap_or(UserAccount {
+            balance: 0,
+            shares: 0,
+            risk_profile: RiskProfile::Conservative,
+            locked_until: 0,
+            last_deposit: 0,
+            total_deposits: 0,
+            total_withdrawals: 0,
+            rewards_earned: 0,
+            referral_code: String::from_str(env, ""),
+        })
+    }
+    
+    /// Internal helper to check an employer's matching ratio and vesting cliff against their caps
+    fn validate_matching(matching_ratio: u32, vesting_cliff: u64) {
+        if matching_ratio > MAX_MATCHING_RATIO {
+            panic!("Matching ratio above cap");
+        }
+        if vesting_cliff > MAX_VESTING_CLIFF {
+            panic!("Vesting cliff above cap");
+        }
+    }
+    
+    /// Employer-matched shares of a member that have not vested yet
+    fn unvested_shares(env: &Env, user: &Address) -> i128 {
+        match Self::get_employment(env.clone(), user.clone()) {
+            Some(employment) if env.ledger().timestamp() < employment.vests_at => employment.matched_shares,
+            _ => 0,
+        }
+    }
+    
+    /// Shares a member cannot withdraw: queued for withdrawal or not yet vested
+    fn held_shares(env: &Env, user: &Address) -> i128 {
+        Self::get_queued_shares(env.clone(), user.clone()) + Self::unvested_shares(env, user)
+    }
+    
+    /// Internal helper to burn a member's unvested employer match tranches without
+    /// fees, returning their KALE value
+    fn forfeit_match(env: &Env, user: &Address) -> i128 {
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let mut user_account = Self::get_user_account(env, user);
+        let before = user_account.clone();
+        let mut sleeve = Self::get_sleeve_state(env, &user_account.risk_profile);
+        Self::accrue_management_fee(env, &config, &mut sleeve);
+        
+        let current_time = env.ledger().timestamp();
+        let mut kept = Vec::new(env);
+        let mut shares = 0i128;
+        let mut locked_until = 0u64;
+        for tranche in Self::load_tranches(env, user, &user_account).iter() {
+            if current_time < tranche.vests_at {
+                shares += tranche.shares;
+            } else {
+                locked_until = locked_until.max(tranche.unlock_at);
+                kept.push_back(tranche);
+            }
+        }
+        env.storage().persistent().set(&(TRANCHES, user.clone()), &kept);
+        user_account.locked_until = locked_until;
+        let value = Self::value_of_shares(&sleeve, shares);
+        
+        sleeve.total_shares -= shares;
+        sleeve.total_value -= value;
+        env.storage().persistent().set(&(SLEEVE, user_account.risk_profile), &sleeve);
+        
+        user_account.shares -= shares;
+        user_account.balance = Self::value_of_shares(&sleeve, user_account.shares);
+        env.storage().persistent().set(user, &user_account);
+        Self::checkpoint_voting_power(env, user);
+        Self::update_member_totals(env, user, &before, &user_account);
+        Self::record_activity(env, user, ActivityKind::Forfeiture, value, shares);
+        
+        Self::add_total_locked(env, -value);
+        
+        value
+    }
+    
+    /// Internal helper to end an enrolment, returning unvested matched shares' value
+    /// to the employer
+    fn end_employment(env: &Env, employee: &Address, employment: &Employment) -> i128 {
+        let mut account = Self::get_employer(env.clone(), employment.employer.clone());
+        
+        let forfeited = if env.ledger().timestamp() < employment.vests_at && employment.matched_shares > 0 {
+            Self::forfeit_match(env, employee)
+        } else {
+            0
+        };
+        env.storage().persistent().remove(&(EMPLOYMENT, employee.clone()));
+        
+        if forfeited > 0 {
+            let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+            let token_client = TokenClient::new(env, &config.kale_token);
+            token_client.transfer(&env.current_contract_address(), &employment.employer, &forfeited);
+            Self::check_reserves(env, &config);
+        }
+        
+        account.employees -= 1;
+        account.total_forfeited += forfeited;
+        env.storage().persistent().set(&(EMPLOYER, employment.employer.clone()), &account);
+        
+        forfeited
+    }
+    
+    /// KALE value of the shares a member can withdraw now
+    fn free_balance(env: &Env, user: &Address) -> i128 {
+        let user_account = Self::get_user_account(env, user);
+        let sleeve = Self::accrued_sleeve(env, &user_account.risk_profile);
+        Self::value_of_shares(&sleeve, user_account.shares - Self::held_shares(env, user))
+    }
+    
+    /// Internal helper to take a queued request out of its epoch and release its shares
+    fn drop_queued_request(env: &Env, request: &WithdrawalRequest) {
+        let mut requests = Self::get_epoch_requests(env, request.epoch);
+        if let Some(index) = requests.first_index_of(request.id) {
+            requests.remove(index);
+        }
+        env.storage().persistent().set(&(EPOCH_REQUESTS, request.epoch), &requests);
+        
+        let queued = Self::get_queued_shares(env.clone(), request.user.clone());
+        env.storage()
+            .persistent()
+            .set(&(QUEUED_SHARES, request.user.clone()), &(queued - request.shares));
+        env.storage().persistent().remove(&(WITHDRAWAL_REQUEST, request.id));
+    }
+    
+    /// Internal helper to clear a member's withdrawal requests: queued ones are
+    /// cancelled and settled ones are marked claimed, returning the KALE they release
+    fn release_withdrawal_requests(env: &Env, user: &Address) -> i128 {
+        let mut released = 0;
+        for request_id in Self::get_withdrawal_requests(env.clone(), user.clone()).iter() {
+            let mut request = Self::get_withdrawal_request(env.clone(), request_id);
+            match request.status.clone() {
+                WithdrawalStatus::Queued => Self::drop_queued_request(env, &request),
+                WithdrawalStatus::Settled(result) => {
+                    released += result.net_amount;
+                    request.status = WithdrawalStatus::Claimed(result);
+                    env.storage().persistent().set(&(WITHDRAWAL_REQUEST, request_id), &request);
+                }
+                WithdrawalStatus::Claimed(_) => {}
+            }
+        }
+        env.storage().persistent().remove(&(WITHDRAWAL_IDS, user.clone()));
+        
+        let reserved: i128 = env.storage().instance().get(&CLAIMS_RESERVED).unwrap_or(0);
+        env.storage().instance().set(&CLAIMS_RESERVED, &(reserved - released));
+        released
+    }
+    
+    /// Internal helper to authorize the compliance officer, falling back to the admin
+    fn require_compliance<T: IntoVal<Env, Val>>(env: &Env, caller: &Address, action: T) {
+        let officer: Option<Address> = env.storage().instance().get(&COMPLIANCE_OFFICER);
+        if Some(caller.clone()) == officer {
+            caller.require_auth();
+        } else {
+            Self::require_admin(env, caller, action);
+        }
+    }
+    
+    /// Why a member may not deposit, if anything stops them
+    fn deposit_block(env: &Env, user: &Address) -> Option<BatchStatus> {
+        if Self::is_frozen(env.clone(), user.clone()) {
+            Some(BatchStatus::Frozen)
+        } else if Self::is_compliance_mode(env.clone())
+            && !Self::has_valid_attestation(env.clone(), user.clone())
+        {
+            Some(BatchStatus::NotVerified)
+        } else {
+            None
+        }
+    }
+    
+    fn require_not_frozen(env: &Env, user: &Address) {
+        if Self::is_frozen(env.clone(), user.clone()) {
+            panic!("Account frozen");
+        }
+    }
+    
+    fn cap_headroom(cap: i128, used: i128) -> i128 {
+        if cap == 0 {
+            i128::MAX
+        } else {
+            (cap - used).max(0)
+        }
+    }
+    
+    /// Internal helper to enforce contribution caps and record the contribution year
+    fn apply_deposit_limits(env: &Env, user: &Address, risk_profile: RiskProfile, amount: i128) {
+        if amount > Self::get_deposit_headroom(env.clone(), user.clone(), risk_profile).available {
+            panic!("Deposit cap exceeded");
+        }
+        
+        let year = env.ledger().timestamp() / SECONDS_PER_YEAR;
+        let mut annual = Self::get_annual_contributions(env.clone(), user.clone());
+        if annual.year != year {
+            annual = AnnualContributions { year, total: 0 };
+        }
+        annual.total += amount;
+        env.storage().persistent().set(&(ANNUAL_CONTRIBUTIONS, user.clone()), &annual);
+    }
+    
+    fn reward_reserve(env: &Env) -> i128 {
+        let match_budgets: i128 = env.storage().instance().get(&MATCH_BUDGETS).unwrap_or(0);
+        Self::get_referral_budget(env.clone()) + match_budgets
+    }
+    
+    /// Internal helper enforcing that KALE paid out to members never dips into the
+    /// treasury, reward reserves or settled claims
+    fn check_reserves(env: &Env, config: &FundConfig) {
+        let token_client = TokenClient::new(env, &config.kale_token);
+        let reserved = Self::get_treasury_balance(env.clone())
+            + Self::reward_reserve(env)
+            + Self::get_claims_reserved(env.clone());
+        if token_client.balance(&env.current_contract_address()) < reserved {
+            panic!("Reserves not covered");
+        }
+    }
+    
+    fn get_member_totals(env: &Env) -> MemberTotals {
+        env.storage().instance().get(&MEMBER_TOTALS).unwrap_or(MemberTotals {
+            invested_members: 0,
+            locked_members: 0,
+            unlock_time_sum: 0,
+            expired_day: 0,
+        })
+    }
+    
+    fn get_unlock_bucket(env: &Env, day: u64) -> UnlockBucket {
+        env.storage()
+            .persistent()
+            .get(&(UNLOCK_BUCKET, day))
+            .unwrap_or(UnlockBucket { members: 0, unlock_time_sum: 0 })
+    }
+    
+    /// Internal helper to drop locks that ended before `today` from the totals,
+    /// deleting their buckets when `remove` is set
+    fn expire_unlock_buckets(env: &Env, totals: &mut MemberTotals, today: u64, remove: bool) {
+        for day in totals.expired_day..today.max(totals.expired_day) {
+            if totals.locked_members == 0 {
+                break;
+            }
+            let bucket = Self::get_unlock_bucket(env, day);
+            if bucket.members == 0 {
+                continue;
+            }
+            totals.locked_members -= bucket.members;
+            totals.unlock_time_sum -= bucket.unlock_time_sum;
+            if remove {
+                env.storage().persistent().remove(&(UNLOCK_BUCKET, day));
+            }
+        }
+        totals.expired_day = totals.expired_day.max(today);
+    }
+    
+    /// Internal helper to add (`sign` 1) or remove (`sign` -1) a member's lock from
+    /// the totals, skipping locks whose day has already expired
+    fn count_unlock(env: &Env, totals: &mut MemberTotals, locked_until: u64, sign: i32) {
+        let day = locked_until / UNLOCK_BUCKET_SIZE;
+        if day < totals.expired_day {
+            return;
+        }
+        let mut bucket = Self::get_unlock_bucket(env, day);
+        bucket.members = bucket.members.checked_add_signed(sign).unwrap();
+        bucket.unlock_time_sum += sign as i128 * locked_until as i128;
+        totals.locked_members = totals.locked_members.checked_add_signed(sign).unwrap();
+        totals.unlock_time_sum += sign as i128 * locked_until as i128;
+        if bucket.members == 0 {
+            env.storage().persistent().remove(&(UNLOCK_BUCKET, day));
+        } else {
+            env.storage().persistent().set(&(UNLOCK_BUCKET, day), &bucket);
+        }
+    }
+    
+    /// Internal helper to keep the member index and totals in step with an account update
+    fn update_member_totals(env: &Env, user: &Address, before: &UserAccount, after: &UserAccount) {
+        if before.total_deposits == 0 && after.total_deposits > 0 {
+            let count = Self::get_member_count(env.clone());
+            env.storage().persistent().set(&(MEMBER, count), user);
+            env.storage().instance().set(&MEMBER_COUNT, &(count + 1));
+        }
+        
+        let mut totals = Self::get_member_totals(env);
+        Self::expire_unlock_buckets(env, &mut totals, env.ledger().timestamp() / UNLOCK_BUCKET_SIZE, true);
+        if before.shares > 0 {
+            totals.invested_members -= 1;
+            Self::count_unlock(env, &mut totals, before.locked_until, -1);
+        }
+        if after.shares > 0 {
+            totals.invested_members += 1;
+            Self::count_unlock(env, &mut totals, after.locked_until, 1);
+        }
+        env.storage().instance().set(&MEMBER_TOTALS, &totals);
+    }
+    
+    /// Internal helper to append to a member's activity log, dropping the oldest entry when full
+    fn record_activity(env: &Env, user: &Address, kind: ActivityKind, amount: i128, shares: i128) {
+        let key = (ACTIVITY, user.clone());
+        let mut log: Vec<ActivityEntry> = env.storage().persistent().get(&key).unwrap_or(Vec::new(env));
+        if log.len() >= MAX_ACTIVITY_ENTRIES {
+            log.remove(0);
+        }
+        
+        let profile = Self::get_user_account(env, user).risk_profile;
+        log.push_back(ActivityEntry {
+            kind,
+            amount,
+            shares,
+            nav_per_share: Self::nav_per_share(&Self::get_sleeve_state(env, &profile)),
+            timestamp: env.ledger().timestamp(),
+        });
+        env.storage().persistent().set(&key, &log);
+    }
+    
+    fn remove_withdrawal_id(env: &Env, user: &Address, request_id: u64) {
+        let mut ids = Self::get_withdrawal_requests(env.clone(), user.clone());
+        if let Some(index) = ids.first_index_of(request_id) {
+            ids.remove(index);
+            env.storage().persistent().set(&(WITHDRAWAL_IDS, user.clone()), &ids);
+        }
+    }
+    
+    fn get_epoch_requests(env: &Env, epoch: u32) -> Vec<u64> {
+        env.storage().persistent().get(&(EPOCH_REQUESTS, epoch)).unwrap_or(Vec::new(env))
+    }
+    
+    /// Internal helper to store a deposit asset, keeping the balances held for members and the treasury
+    fn store_deposit_asset(env: &Env, asset: &Symbol, token: &Address, spread: u32) {
+        let key = (DEPOSIT_ASSET, asset.clone());
+        let (balance, treasury) = match env.storage().persistent().get::<_, DepositAsset>(&key) {
+            Some(existing) => {
+                if existing.token != *token && (existing.balance > 0 || existing.treasury > 0) {
+                    panic!("Asset still held");
+                }
+                (existing.balance, existing.treasury)
+            }
+            None => {
+                let mut assets = Self::get_deposit_assets(env.clone());
+                assets.push_back(asset.clone());
+                env.storage().instance().set(&DEPOSIT_ASSETS, &assets);
+                (0, 0)
+            }
+        };
+        env.storage()
+            .persistent()
+            .set(&key, &DepositAsset { token: token.clone(), spread, balance, treasury });
+        
+        log!(env, "Deposit asset registered: asset={}, token={}, spread={}", asset, token, spread);
+    }
+    
+    /// Internal helper returning fresh USD prices of an asset and of KALE
+    fn asset_prices(env: &Env, asset: &Symbol) -> (i128, i128) {
+        let oracle: Address = env
+            .storage()
+            .instance()
+            .get(&ORACLE)
+            .unwrap_or_else(|| panic!("Oracle not set"));
+        let oracle_client = PriceOracleClient::new(env, &oracle);
+        
+        let asset_price = oracle_client
+            .get_fresh_price(asset)
+            .unwrap_or_else(|| panic!("Stale price"))
+            .price_usd;
+        let kale_price = oracle_client
+            .get_fresh_price(&symbol_short!("KALE"))
+            .unwrap_or_else(|| panic!("Stale price"))
+            .price_usd;
+        if asset_price <= 0 || kale_price <= 0 {
+            panic!("Invalid price");
+        }
+        
+        (asset_price, kale_price)
+    }
+    
+    /// Internal helper to authorize an admin action, with signer approvals when multisig is on
+    fn require_admin<T: IntoVal<Env, Val>>(env: &Env, caller: &Address, action: T) {
+        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
+        multisig::require_admin(env, &admin, caller, action);
+    }
+    
+    fn apply_fee_update(mut config: FundConfig, update: &FeeUpdate) -> FundConfig {
+        if let Some(w_fee) = update.withdrawal_fee {
+            config.withdrawal_fee = w_fee;
+        }
+        if let Some(p_fee) = update.performance_fee {
+            config.performance_fee = p_fee;
+        }
+        if let Some(penalty) = update.early_withdrawal_penalty {
+            config.early_withdrawal_penalty = penalty;
+        }
+        if let Some(m_fee) = update.management_fee {
+            config.management_fee = m_fee;
+        }
+        config
+    }
+    
+    /// Internal helper to record a member's voting power, and the total it is part
+    /// of, from the current ledger on
+    ///
+    /// Both sides are invested principal, so quorum compares like with like.
+    fn checkpoint_voting_power(env: &Env, user: &Address) {
+        let mut weight = 0i128;
+        for tranche in Self::get_tranches(env.clone(), user.clone()).iter() {
+            weight += tranche.principal;
+        }
+        let previous = Self::voting_power_at(env, user, u32::MAX);
+        
+        let key = (VOTING_POWER, user.clone());
+        let mut checkpoints: Vec<Checkpoint> = env.storage().persistent().get(&key).unwrap_or(Vec::new(env));
+        Self::push_checkpoint(env, &mut checkpoints, weight);
+        env.storage().persistent().set(&key, &checkpoints);
+        
+        let total = Self::total_voting_power_at(env, u32::MAX) - previous + weight;
+        let mut checkpoints: Vec<Checkpoint> = env
+            .storage()
+            .persistent()
+            .get(&TOTAL_VOTING_POWER)
+            .unwrap_or(Vec::new(env));
+        Self::push_checkpoint(env, &mut checkpoints, total);
+        env.storage().persistent().set(&TOTAL_VOTING_POWER, &checkpoints);
+    }
+    
+    fn push_checkpoint(env: &Env, checkpoints: &mut Vec<Checkpoint>, weight: i128) {
+        let ledger = env.ledger().sequence();
+        let checkpoint = Checkpoint { ledger, weight };
+        
+        // Several updates in one ledger keep only the last value
+        match checkpoints.last() {
+            Some(last) if last.ledger == ledger => {
+                checkpoints.set(checkpoints.len() - 1, checkpoint);
+            }
+            _ => checkpoints.push_back(checkpoint),
+        }
+    }
+    
+    fn voting_power_at(env: &Env, user: &Address, ledger: u32) -> i128 {
+        let checkpoints: Vec<Checkpoint> = env
+            .storage()
+            .persistent()
+            .get(&(VOTING_POWER, user.clone()))
+            .unwrap_or(Vec::new(env));
+        Self::checkpoint_at(&checkpoints, ledger)
+    }
+    
+    fn total_voting_power_at(env: &Env, ledger: u32) -> i128 {
+        let checkpoints: Vec<Checkpoint> = env
+            .storage()
+            .persistent()
+            .get(&TOTAL_VOTING_POWER)
+            .unwrap_or(Vec::new(env));
+        Self::checkpoint_at(&checkpoints, ledger)
+    }
+    
+    /// Weight of the latest checkpoint at or before `ledger`
+    fn checkpoint_at(checkpoints: &Vec<Checkpoint>, ledger: u32) -> i128 {
+        let mut i = checkpoints.len();
+        while i > 0 {
+            i -= 1;
+            let checkpoint = checkpoints.get(i).unwrap();
+            if checkpoint.ledger <= ledger {
+                return checkpoint.weight;
+            }
+        }
+        0
+    }
+    
+    fn apply_config_update(mut config: FundConfig, update: &ConfigUpdate) -> FundConfig {
+        if let Some(min_dep) = update.min_deposit {
+            config.min_deposit = min_dep;
+        }
+        if let Some(max_dep) = update.max_deposit {
+            config.max_deposit = max_dep;
+        }
+        if let Some(w_fee) = update.withdrawal_fee {
+            config.withdrawal_fee = w_fee;
+        }
+        if let Some(p_fee) = update.performance_fee {
+            config.performance_fee = p_fee;
+        }
+        if let Some(penalty) = update.early_withdrawal_penalty {
+            config.early_withdrawal_penalty = penalty;
+        }
+        // Existing tranches keep their unlock time; only new deposits use the new period
+        if let Some(period) = update.lock_period {
+            config.lock_period = period;
+        }
+        if let Some(bonus) = update.referral_bonus {
+            config.referral_bonus = bonus;
+        }
+        config
+    }
+    
+    /// Internal helper enforcing the invariants every fund configuration must satisfy
+    fn validate_config(config: &FundConfig) {
+        if config.min_deposit <= 0 || config.max_deposit < config.min_deposit {
+            panic!("Invalid deposit limits");
+        }
+        if config.lock_period > MAX_LOCK_PERIOD {
+            panic!("Lock period too long");
+        }
+        if config.withdrawal_fee > MAX_WITHDRAWAL_FEE {
+            panic!("Withdrawal fee above cap");
+        }
+        if config.performance_fee > MAX_PERFORMANCE_FEE {
+            panic!("Performance fee above cap");
+        }
+        if config.early_withdrawal_penalty > MAX_EARLY_WITHDRAWAL_PENALTY {
+            panic!("Early withdrawal penalty above cap");
+        }
+        if config.referral_bonus > MAX_REFERRAL_BONUS {
+            panic!("Referral bonus above cap");
+        }
+        if config.management_fee > MAX_MANAGEMENT_FEE {
+            panic!("Management fee above cap");
+        }
+        // Keeps `net_amount` in `withdraw` non-negative
+        if config.withdrawal_fee + config.early_withdrawal_penalty > 10000 {
+            panic!("Fee and penalty exceed 100%");
+        }
+    }
+    
+    /// Internal helper to credit KALE already received by the contract to a member
+    ///
+    /// Issues sleeve shares as a new lock tranche, records the referrer on the
+    /// member's first deposit and pays referral rewards from the referral budget.
+    fn credit_deposit(
+        env: &Env,
+        config: &FundConfig,
+        user: &Address,
+        amount: i128,
+        risk_profile: RiskProfile,
+        referral: Option<Address>,
+    ) -> DepositResult {
+        let result = Self::credit_account(env, config, user, amount, risk_profile, referral);
+        Self::add_total_locked(env, amount);
+        result
+    }
+    
+    /// Internal helper to adjust the total locked value (member funds held in KALE)
+    fn add_total_locked(env: &Env, amount: i128) {
+        if amount == 0 {
+            return;
+        }
+        let mut total_locked: i128 = env.storage().instance().get(&TOTAL_LOCKED).unwrap_or(0);
+        total_locked += amount;
+        env.storage().instance().set(&TOTAL_LOCKED, &total_locked);
+    }
+    
+    /// Internal helper to issue shares for a deposit already received, leaving the
+    /// total locked value to the caller
+    fn credit_account(
+        env: &Env,
+        config: &FundConfig,
+        user: &Address,
+        amount: i128,
+        risk_profile: RiskProfile,
+        referral: Option<Address>,
+    ) -> DepositResult {
         // Get or create user account
-        let mut user_account = Self::get_user_account(&env, &user);
+        let mut user_account = Self::get_user_account(env, user);
+        let before = user_account.clone();
+        
+        // Members hold shares in a single sleeve; switching is not a side effect of depositing
+        if user_account.shares > 0 && user_account.risk_profile != risk_profile {
+            panic!("Risk profile mismatch");
+        }
+        match Self::deposit_block(env, user) {
+            Some(BatchStatus::Frozen) => panic!("Account frozen"),
+            Some(_) => panic!("Attestation required"),
+            None => {}
+        }
+        Self::apply_deposit_limits(env, user, risk_profile, amount);
+        
+        // The first deposit fixes the member's referrer and referral code
+        if user_account.total_deposits == 0 {
+            if let Some(referrer) = referral {
+                Self::register_referrer(env, user, &referrer);
+            }
+            user_account.referral_code = Self::assign_referral_code(env, user);
+        }
+        let referral_bonus = Self::pay_referral_rewards(env, config, user, amount);
+        
         let current_time = env.ledger().timestamp();
         
+        // Issue sleeve shares at the current NAV
+        let mut sleeve = Self::get_sleeve_state(env, &risk_profile);
+        Self::accrue_management_fee(env, config, &mut sleeve);
+        let shares_issued = Self::shares_for_value(&sleeve, amount);
+        sleeve.total_shares += shares_issued;
+        sleeve.total_value += amount;
+        env.storage().persistent().set(&(SLEEVE, risk_profile), &sleeve);
+        
+        // Each deposit is its own tranche; earlier tranches keep their unlock time
+        let unlock_at = current_time + config.lock_period;
+        let mut tranches = Self::load_tranches(env, user, &user_account);
+        tranches.push_back(DepositTranche {
+            shares: shares_issued,
+            principal: amount,
+            deposited_at: current_time,
+            unlock_at,
+            vests_at: 0,
+        });
+        env.storage().persistent().set(&(TRANCHES, user.clone()), &tranches);
+        
         // Update user account
-        user_account.balance += amount;
+        user_account.shares += shares_issued;
+        user_account.balance = Self::value_of_shares(&sleeve, user_account.shares);
         user_account.risk_profile = risk_profile;
-        user_account.locked_until = current_time + config.lock_period;
+        user_account.locked_until = user_account.locked_until.max(unlock_at);
         user_account.last_deposit = current_time;
         user_account.total_deposits += amount;
         
         // Store updated account
-        env.storage().persistent().set(&user, &user_account);
-        
-        // Update total locked value
-        let mut total_locked: i128 = env.storage().instance().get(&TOTAL_LOCKED).unwrap_or(0);
-        total_locked += amount;
-        env.storage().instance().set(&TOTAL_LOCKED, &total_locked);
+        env.storage().persistent().set(user, &user_account);
+        Self::checkpoint_voting_power(env, user);
+        Self::update_member_totals(env, user, &before, &user_account);
+        Self::record_activity(env, user, ActivityKind::Deposit, amount, shares_issued);
         
         log!(
-            &env,
-            "Deposit: user={}, amount={}, new_balance={}, lock_until={}",
+            env,
+            "Deposit: user={}, amount={}, shares={}, new_balance={}, lock_until={}",
             user,
             amount,
+            shares_issued,
             user_account.balance,
-            user_account.locked_until
+            unlock_at
         );
         
         DepositResult {
             user: user.clone(),
             amount,
             new_balance: user_account.balance,
-            lock_until: user_account.locked_until,
+            shares_issued,
+            lock_until: unlock_at,
             referral_bonus,
         }
     }
     
-    /// Withdraw KALE tokens from the pension fund
-    pub fn withdraw(env: Env, user: Address, amount: i128) -> WithdrawalResult {
-        user.require_auth();
+    /// Internal helper to record a member's referrer, rejecting self and circular referrals
+    fn register_referrer(env: &Env, user: &Address, referrer: &Address) {
+        if referrer == user {
+            panic!("Self-referral not allowed");
+        }
+        if Self::get_user_account(env, referrer).total_deposits == 0 {
+            panic!("Referrer is not a member");
+        }
         
-        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
-        let mut user_account = Self::get_user_account(&env, &user);
+        // Walk up the referrer's chain; finding the new member there would close a loop
+        let mut ancestor = Some(referrer.clone());
+        let mut depth = 0u32;
+        while let Some(current) = ancestor {
+            if current == *user {
+                panic!("Circular referral");
+            }
+            depth += 1;
+            if depth > MAX_REFERRAL_DEPTH {
+                break;
+            }
+            ancestor = env.storage().persistent().get(&(REFERRER, current));
+        }
+        
+        env.storage().persistent().set(&(REFERRER, user.clone()), referrer);
+        
+        let mut stats = Self::get_referral_stats(env.clone(), referrer.clone());
+        stats.referred_members += 1;
+        env.storage().persistent().set(&(REFERRAL_STATS, referrer.clone()), &stats);
+    }
+    
+    /// Internal helper to pay referral rewards up the member's referrer chain
+    ///
+    /// Tier 1 earns `referral_bonus`, further tiers earn the configured tier bonuses.
+    /// Rewards come out of the funded referral budget and stop when it runs out.
+    fn pay_referral_rewards(env: &Env, config: &FundConfig, user: &Address, amount: i128) -> i128 {
+        let mut referrer: Option<Address> = env.storage().persistent().get(&(REFERRER, user.clone()));
+        if referrer.is_none() {
+            return 0;
+        }
+        
+        let tier_bonuses = Self::get_referral_tiers(env.clone());
+        let mut budget = Self::get_referral_budget(env.clone());
+        let token_client = TokenClient::new(env, &config.kale_token);
+        let mut total_paid = 0i128;
+        let mut tier = 0u32;
+        
+        while let Some(current) = referrer {
+            let bonus_bps = if tier == 0 {
+                config.referral_bonus
+            } else {
+                match tier_bonuses.get(tier - 1) {
+                    Some(bps) => bps,
+                    None => break,
+                }
+            };
+            
+            let reward = ((amount * bonus_bps as i128) / 10000).min(budget);
+            let mut stats = Self::get_referral_stats(env.clone(), current.clone());
+            if tier == 0 {
+                stats.referred_volume += amount;
+            }
+            if reward > 0 {
+                token_client.transfer(&env.current_contract_address(), &current, &reward);
+                budget -= reward;
+                total_paid += reward;
+                stats.rewards_earned += reward;
+                Self::record_activity(env, &current, ActivityKind::Reward, reward, 0);
+            }
+            env.storage().persistent().set(&(REFERRAL_STATS, current.clone()), &stats);
+            
+            tier += 1;
+            referrer = env.storage().persistent().get(&(REFERRER, current));
+        }
+        
+        env.storage().instance().set(&REFERRAL_BUDGET, &budget);
+        
+        total_paid
+    }
+    
+    /// Internal helper to give a new member a default referral code ("KPF" + member number)
+    fn assign_referral_code(env: &Env, user: &Address) -> String {
+        let sequence: u32 = env.storage().instance().get(&REFERRAL_SEQUENCE).unwrap_or(0) + 1;
+        env.storage().instance().set(&REFERRAL_SEQUENCE, &sequence);
+        
+        let mut buf = [0u8; 13];
+        buf[..3].copy_from_slice(b"KPF");
+        let mut digits = [0u8; 10];
+        let mut len = 0;
+        let mut n = sequence;
+        loop {
+            digits[len] = b'0' + (n % 10) as u8;
+            len += 1;
+            n /= 10;
+            if n == 0 {
+                break;
+            }
+        }
+        for i in 0..len {
+            buf[3 + i] = digits[len - 1 - i];
+        }
+        
+        let code = String::from_bytes(env, &buf[..3 + len]);
+        env.storage().persistent().set(&(REFERRAL_CODE, code.clone()), user);
+        code
+    }
+    
+    /// Internal helper to record owner activity, which cancels any pending inheritance claim
+    fn mark_active(env: &Env, user: &Address) {
+        env.storage()
+            .persistent()
+            .set(&(LAST_ACTIVE, user.clone()), &env.ledger().timestamp());
         
-        if user_account.balance < amount {
+        if env.storage().persistent().has(&(INHERIT_CLAIM, user.clone())) {
+            env.storage().persistent().remove(&(INHERIT_CLAIM, user.clone()));
+            log!(env, "Inheritance claim cancelled by owner: {}", user);
+        }
+    }
+    
+    /// Internal helper to burn a member's shares for `amount` KALE of value
+    ///
+    /// Burns the shares worth `amount` at the sleeve's current NAV, or exactly `shares`
+    /// when the caller has priced them itself. Applies the withdrawal fee and the early withdrawal penalty (reduced by
+    /// `penalty_waiver` basis points, waiving at most `waiver_cap` KALE) and updates
+    /// the sleeve, tranches and account. Paying out `net_amount` is left to the caller.
+    fn redeem(
+        env: &Env,
+        config: &FundConfig,
+        user: &Address,
+        amount: i128,
+        shares: Option<i128>,
+        penalty_waiver: u32,
+        waiver_cap: i128,
+    ) -> WithdrawalResult {
+        Self::require_not_frozen(env, user);
+        let mut user_account = Self::get_user_account(env, user);
+        let before = user_account.clone();
+        let mut sleeve = Self::get_sleeve_state(env, &user_account.risk_profile);
+        Self::accrue_management_fee(env, config, &mut sleeve);
+        
+        // Shares queued for withdrawal or not yet vested are held back
+        let held = Self::held_shares(env, user);
+        let free_shares = user_account.shares - held;
+        let sufficient = match shares {
+            Some(shares) => shares > 0 && shares <= free_shares && amount <= sleeve.total_value,
+            None => Self::value_of_shares(&sleeve, free_shares) >= amount,
+        };
+        if amount <= 0 || !sufficient {
             panic!("Insufficient balance");
         }
         
         let current_time = env.ledger().timestamp();
-        let mut fee = 0i128;
-        let mut penalty = 0i128;
+        
+        // Burn sleeve shares for the gross amount, rounding in the fund's favour
+        let shares_burned = shares.unwrap_or_else(|| Self::shares_to_burn(&sleeve, amount).min(user_account.shares));
+        let locked_shares_burned =
+            Self::consume_tranches(env, user, &mut user_account, shares_burned, current_time);
         
         // Calculate withdrawal fee
-        fee = (amount * config.withdrawal_fee as i128) / 10000;
+        let fee = (amount * config.withdrawal_fee as i128) / 10000;
         
-        // Calculate early withdrawal penalty if still locked
-        if current_time < user_account.locked_until {
-            penalty = (amount * config.early_withdrawal_penalty as i128) / 10000;
-        }
+        // Calculate early withdrawal penalty on the value taken from locked tranches
+        let locked_amount = (amount * locked_shares_burned) / shares_burned;
+        let full_penalty = (locked_amount * config.early_withdrawal_penalty as i128) / 10000;
+        let penalty_waived = ((full_penalty * penalty_waiver as i128) / 10000).min(waiver_cap);
+        let penalty = full_penalty - penalty_waived;
         
         let net_amount = amount - fee - penalty;
         
+        // Fees go to the treasury; penalties either go to the treasury or stay in the
+        // sleeve, raising its NAV for the members who remain invested
+        let mut ledger = Self::get_treasury_ledger(env.clone());
+        ledger.withdrawal_fees += fee;
+        let redistributed = if Self::is_penalty_redistributed(env.clone()) {
+            penalty
+        } else {
+            ledger.early_withdrawal_penalties += penalty;
+            0
+        };
+        env.storage().instance().set(&TREASURY_LEDGER, &ledger);
+        
+        sleeve.total_shares -= shares_burned;
+        sleeve.total_value -= amount - redistributed;
+        env.storage().persistent().set(&(SLEEVE, user_account.risk_profile), &sleeve);
+        
         // Update user account
-        user_account.balance -= amount;
+        user_account.shares -= shares_burned;
+        user_account.balance = Self::value_of_shares(&sleeve, user_account.shares);
         user_account.total_withdrawals += amount;
         
         // Store updated account
-        env.storage().persistent().set(&user, &user_account);
-        
-        // Update total locked value
-        let mut total_locked: i128 = env.storage().instance().get(&TOTAL_LOCKED).unwrap_or(0);
-        total_locked -= amount;
-        env.storage().instance().set(&TOTAL_LOCKED, &total_locked);
-        
-        // Transfer tokens back to user
-        let token_client = TokenClient::new(&env, &config.kale_token);
-        token_client.transfer(&env.current_contract_address(), &user, &net_amount);
+        env.storage().persistent().set(user, &user_account);
+        Self::checkpoint_voting_power(env, user);
+        Self::update_member_totals(env, user, &before, &user_account);
+        Self::record_activity(env, user, ActivityKind::Withdrawal, amount, shares_burned);
+        if fee > 0 {
+            Self::record_activity(env, user, ActivityKind::Fee, fee, 0);
+        }
+        if penalty > 0 {
+            Self::record_activity(env, user, ActivityKind::Penalty, penalty, 0);
+        }
         
-        log!(
-            &env,
-            "Withdrawal: user={}, amount={}, fee={}, penalty={}, net_amount={}",
-            user,
-            amount,
-            fee,
-            penalty,
-            net_amount
-        );
+        // Everything but a redistributed penalty leaves member funds: the net amount
+        // to the member, the fee and any other penalty to the treasury
+        Self::add_total_locked(env, -(amount - redistributed));
         
         WithdrawalResult {
             user: user.clone(),
             amount,
             fee,
             penalty,
+            penalty_waived,
             net_amount,
+            shares_burned,
             new_balance: user_account.balance,
         }
     }
     
-    /// Get user account information
-    pub fn get_account(env: Env, user: Address) -> UserAccount {
-        Self::get_user_account(&env, &user)
-    }
-    
-    /// Get fund configuration
-    pub fn get_config(env: Env) -> FundConfig {
-        env.storage().instance().get(&FUND_CONFIG).unwrap()
+    /// Internal helper to get a member's tranches
+    ///
+    /// Accounts created before tranches existed are treated as one tranche
+    /// unlocking at `locked_until`.
+    fn load_tranches(env: &Env, user: &Address, user_account: &UserAccount) -> Vec<DepositTranche> {
+        let tranches: Vec<DepositTranche> = env
+            .storage()
+            .persistent()
+            .get(&(TRANCHES, user.clone()))
+            .unwrap_or(Vec::new(env));
+        
+        if tranches.is_empty() && user_account.shares > 0 {
+            let mut legacy = Vec::new(env);
+            legacy.push_back(DepositTranche {
+                shares: user_account.shares,
+                principal: user_account.total_deposits - user_account.total_withdrawals,
+                deposited_at: user_account.last_deposit,
+                unlock_at: user_account.locked_until,
+                vests_at: 0,
+            });
+            return legacy;
+        }
+        
+        tranches
     }
     
-    /// Get total value locked in the fund
-    pub fn get_total_locked(env: Env) -> i128 {
-        env.storage().instance().get(&TOTAL_LOCKED).unwrap_or(0)
+    /// Remove `shares` from a member's tranches, unlocked tranches first (FIFO)
+    /// then locked tranches (FIFO), never touching unvested employer matches.
+    /// Returns how many of the shares were locked.
+    fn consume_tranches(
+        env: &Env,
+        user: &Address,
+        user_account: &mut UserAccount,
+        shares: i128,
+        current_time: u64,
+    ) -> i128 {
+        let mut tranches = Self::load_tranches(env, user, user_account);
+        let mut remaining = shares;
+        let mut locked_consumed = 0i128;
+        
+        // First pass takes unlocked tranches, second pass takes locked ones
+        for take_locked in [false, true] {
+            for i in 0..tranches.len() {
+                if remaining == 0 {
+                    break;
+                }
+                
+                let mut tranche = tranches.get(i).unwrap();
+                let is_locked = current_time < tranche.unlock_at;
+                if is_locked != take_locked || tranche.shares == 0 || current_time < tranche.vests_at {
+                    continue;
+                }
+                
+                let taken = remaining.min(tranche.shares);
+                tranche.principal -= (tranche.principal * taken) / tranche.shares;
+                tranche.shares -= taken;
+                tranches.set(i, tranche);
+                
+                remaining -= taken;
+                if is_locked {
+                    locked_consumed += taken;
+                }
+            }
+        }
+        
+        let mut kept = Vec::new(env);
+        let mut locked_until = 0u64;
+        for tranche in tranches.iter() {
+            if tranche.shares > 0 {
+                locked_until = locked_until.max(tranche.unlock_at);
+                kept.push_back(tranche);
+            }
+        }
+        
+        user_account.locked_until = locked_until;
+        env.storage().persistent().set(&(TRANCHES, user.clone()), &kept);
+        
+        locked_consumed
     }
     
-    /// Update fund configuration (admin only)
-    pub fn update_config(
-        env: Env,
-        caller: Address,
-        min_deposit: Option<i128>,
-        max_deposit: Option<i128>,
-        withdrawal_fee: Option<u32>,
-        performance_fee: Option<u32>,
-        early_withdrawal_penalty: Option<u32>,
-    ) {
-        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
-        if caller != admin {
-            panic!("Unauthorized");
+    /// Re-denominate a member's tranches after their shares were converted
+    fn rescale_tranches(env: &Env, user: &Address, user_account: &UserAccount, new_shares: i128) {
+        let tranches = Self::load_tranches(env, user, user_account);
+        let mut rescaled = Vec::new(env);
+        let mut allocated = 0i128;
+        
+        for (i, tranche) in tranches.iter().enumerate() {
+            // The last tranche absorbs rounding so the total matches exactly
+            let shares = if i as u32 == tranches.len() - 1 {
+                new_shares - allocated
+            } else if user_account.shares > 0 {
+                (tranche.shares * new_shares) / user_account.shares
+            } else {
+                0
+            };
+            allocated += shares;
+            rescaled.push_back(DepositTranche {
+                shares,
+                principal: tranche.principal,
+                deposited_at: tranche.deposited_at,
+                unlock_at: tranche.unlock_at,
+                vests_at: tranche.vests_at,
+            });
         }
-        caller.require_auth();
         
-        let mut config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        env.storage().persistent().set(&(TRANCHES, user.clone()), &rescaled);
+    }
+    
+    /// Internal helper to get a sleeve, empty if no member has joined it yet
+    fn get_sleeve_state(env: &Env, profile: &RiskProfile) -> Sleeve {
+        env.storage().persistent().get(&(SLEEVE, *profile)).unwrap_or(Sleeve {
+            profile: *profile,
+            total_shares: 0,
+            total_value: 0,
+            last_valuation: 0,
+            high_water_mark: NAV_SCALE,
+            last_fee_accrual: 0,
+        })
+    }
+    
+    fn get_fee_accrual(env: &Env, profile: &RiskProfile) -> FeeAccrual {
+        env.storage().persistent().get(&(FEE_ACCRUAL, *profile)).unwrap_or(FeeAccrual {
+            profile: *profile,
+            fee_shares: 0,
+            performance_fees: 0,
+            management_fees: 0,
+            last_crystallization: 0,
+        })
+    }
+    
+    /// Internal helper to dilute a sleeve by the management fee accrued since its last accrual
+    ///
+    /// Persists the treasury's fee accrual; persisting `sleeve` is left to the caller.
+    fn accrue_management_fee(env: &Env, config: &FundConfig, sleeve: &mut Sleeve) {
+        let current_time = env.ledger().timestamp();
+        let (fee_value, fee_shares) = Self::pending_management_fee(config, sleeve, current_time);
         
-        if let Some(min_dep) = min_deposit {
-            config.min_deposit = min_dep;
+        if fee_shares > 0 {
+            sleeve.total_shares += fee_shares;
+            
+            let mut accrual = Self::get_fee_accrual(env, &sleeve.profile);
+            accrual.fee_shares += fee_shares;
+            accrual.management_fees += fee_value;
+            env.storage().persistent().set(&(FEE_ACCRUAL, sleeve.profile), &accrual);
         }
-        if let Some(max_dep) = max_deposit {
-            config.max_deposit = max_dep;
+        
+        sleeve.last_fee_accrual = current_time;
+    }
+    
+    /// Management fee owed since the sleeve's last accrual, as (value, shares to mint)
+    fn pending_management_fee(config: &FundConfig, sleeve: &Sleeve, current_time: u64) -> (i128, i128) {
+        let elapsed = current_time.saturating_sub(sleeve.last_fee_accrual);
+        if elapsed == 0 || config.management_fee == 0 {
+            return (0, 0);
         }
-        if let Some(w_fee) = withdrawal_fee {
-            config.withdrawal_fee = w_fee;
+        let fee_value = (sleeve.total_value * config.management_fee as i128 * elapsed as i128)
+            / (10000 * SECONDS_PER_YEAR as i128);
+        (fee_value, Self::dilution_shares(sleeve, fee_value))
+    }
+    
+    /// Internal helper to read a sleeve as it stands once the pending management fee
+    /// is taken, without recording the accrual
+    fn accrued_sleeve(env: &Env, profile: &RiskProfile) -> Sleeve {
+        let config: FundConfig = env.storage().instance().get(&FUND_CONFIG).unwrap();
+        let mut sleeve = Self::get_sleeve_state(env, profile);
+        let current_time = env.ledger().timestamp();
+        sleeve.total_shares += Self::pending_management_fee(&config, &sleeve, current_time).1;
+        sleeve.last_fee_accrual = current_time;
+        sleeve
+    }
+    
+    fn accrue_all_management_fees(env: &Env, config: &FundConfig) {
+        for profile in [RiskProfile::Conservative, RiskProfile::Moderate, RiskProfile::Aggressive] {
+            let mut sleeve = Self::get_sleeve_state(env, &profile);
+            Self::accrue_management_fee(env, config, &mut sleeve);
+            env.storage().persistent().set(&(SLEEVE, profile), &sleeve);
         }
-        if let Some(p_fee) = performance_fee {
-            config.performance_fee = p_fee;
+    }
+    
+    /// Shares to mint so that they are worth `fee_value` after dilution
+    fn dilution_shares(sleeve: &Sleeve, fee_value: i128) -> i128 {
+        if fee_value <= 0 || sleeve.total_value <= fee_value {
+            return 0;
         }
-        if let Some(penalty) = early_withdrawal_penalty {
-            config.early_withdrawal_penalty = penalty;
+        (fee_value * sleeve.total_shares) / (sleeve.total_value - fee_value)
+    }
+    
+    fn nav_per_share(sleeve: &Sleeve) -> i128 {
+        if sleeve.total_shares == 0 {
+            NAV_SCALE
+        } else {
+            (sleeve.total_value * NAV_SCALE) / sleeve.total_shares
         }
-        
-        env.storage().instance().set(&FUND_CONFIG, &config);
-        
-        log!(&env, "Fund config updated by admin: {}", caller);
     }
     
-    /// Distribute rewards to users (admin only)
-    pub fn distribute_rewards(env: Env, caller: Address, total_rewards: i128) {
-        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
-        if caller != admin {
-            panic!("Unauthorized");
+    /// Shares issued for `value` KALE (rounded down)
+    fn shares_for_value(sleeve: &Sleeve, value: i128) -> i128 {
+        if sleeve.total_shares == 0 {
+            return value;
         }
-        caller.require_auth();
-        
-        let total_locked: i128 = env.storage().instance().get(&TOTAL_LOCKED).unwrap_or(0);
-        if total_locked == 0 {
-            return;
+        if sleeve.total_value <= 0 {
+            panic!("Sleeve has no value");
         }
-        
-        // Rewards distribution logic would be implemented here
-        // This would iterate through all users and distribute proportional rewards
-        
-        log!(&env, "Rewards distributed: total={}", total_rewards);
+        (value * sleeve.total_shares) / sleeve.total_value
     }
     
-    /// Internal helper to get user account
-    fn get_user_account(env: &Env, user: &Address) -> UserAccount {
-        env.storage().persistent().get(user).unwrap_or(UserAccount {
-            balance: 0,
-            risk_profile: RiskProfile::Conservative,
-            locked_until: 0,
-            last_deposit: 0,
-            total_deposits: 0,
-            total_withdrawals: 0,
-            rewards_earned: 0,
-            referral_code: String::from_str(env, ""),
-        })
+    /// Shares that must be burned to release `value` KALE (rounded up)
+    fn shares_to_burn(sleeve: &Sleeve, value: i128) -> i128 {
+        if sleeve.total_value <= 0 {
+            panic!("Sleeve has no value");
+        }
+        (value * sleeve.total_shares + sleeve.total_value - 1) / sleeve.total_value
+    }
+    
+    fn value_of_shares(sleeve: &Sleeve, shares: i128) -> i128 {
+        if sleeve.total_shares == 0 {
+            return 0;
+        }
+        (shares * sleeve.total_value) / sleeve.total_shares
     }
 }
@@ -1,60 +1,70 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{testutils::Address as _, Address, Env, String};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token, vec, xdr::ToXdr, Address, Env, String,
+};
 
-#[test]
-fn test_initialize_contract() {
-    let env = Env::default();
+fn setup(env: &Env, lock_period: u64) -> (PensionFundContractClient<'_>, Address, Address) {
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, PensionFundContract);
-    let client = PensionFundContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
-    let kale_token = Address::generate(&env);
+    let client = PensionFundContractClient::new(env, &contract_id);
     
+    let admin = Address::generate(env);
+    let kale_token = env.register_stellar_asset_contract_v2(admin.clone()).address();
     client.initialize(
         &admin,
         &kale_token,
         &1000000, // min_deposit: 1 KALE
         &10000000000, // max_deposit: 10,000 KALE
-        &2592000, // lock_period: 30 days
+        &lock_period,
         &100, // withdrawal_fee: 1%
         &200, // performance_fee: 2%
         &500, // early_withdrawal_penalty: 5%
         &50, // referral_bonus: 0.5%
     );
     
+    (client, admin, kale_token)
+}
+
+fn member(env: &Env, kale_token: &Address, amount: i128) -> Address {
+    let user = Address::generate(env);
+    token::StellarAssetClient::new(env, kale_token).mint(&user, &amount);
+    user
+}
+
+fn execute_queued(env: &Env, client: &PensionFundContractClient, change_id: u64) {
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp += client.get_timelock_delay();
+    });
+    client.execute_change(&change_id);
+}
+
+#[test]
+fn test_initialize_contract() {
+    let env = Env::default();
+    let (client, _, kale_token) = setup(&env, 2592000);
+    
     let config = client.get_config();
     assert_eq!(config.kale_token, kale_token);
     assert_eq!(config.min_deposit, 1000000);
     assert_eq!(config.lock_period, 2592000);
+    
+    // A second initialize cannot take over the admin or swap the token
+    let attacker = Address::generate(&env);
+    let fake_token = env.register_stellar_asset_contract_v2(attacker.clone()).address();
+    assert!(client
+        .try_initialize(&attacker, &fake_token, &1000000, &10000000000, &0, &100, &200, &500, &50)
+        .is_err());
+    assert_eq!(client.get_config().kale_token, kale_token);
 }
 
 #[test]
 fn test_deposit_success() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, PensionFundContract);
-    let client = PensionFundContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
-    let user = Address::generate(&env);
-    let kale_token = Address::generate(&env);
-    
-    // Initialize contract
-    client.initialize(
-        &admin,
-        &kale_token,
-        &1000000,
-        &10000000000,
-        &2592000,
-        &100,
-        &200,
-        &500,
-        &50,
-    );
-    
-    // Mock token contract for testing
-    env.mock_all_auths();
+    let (client, _, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
     
     let deposit_amount = 5000000; // 5 KALE
     let result = client.deposit(
@@ -77,27 +87,14 @@ fn test_deposit_success() {
 #[test]
 fn test_deposit_with_referral() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, PensionFundContract);
-    let client = PensionFundContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
-    let user = Address::generate(&env);
-    let referrer = Address::generate(&env);
-    let kale_token = Address::generate(&env);
-    
-    client.initialize(
-        &admin,
-        &kale_token,
-        &1000000,
-        &10000000000,
-        &2592000,
-        &100,
-        &200,
-        &500,
-        &50, // 0.5% referral bonus
-    );
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
+    let referrer = member(&env, &kale_token, 100000000);
+    token::StellarAssetClient::new(&env, &kale_token).mint(&admin, &100000000);
     
-    env.mock_all_auths();
+    // Referrers must be members and rewards come from a funded budget
+    client.deposit(&referrer, &1000000, &RiskProfile::Moderate, &None::<Address>);
+    client.fund_referral_budget(&admin, &1000000);
     
     let deposit_amount = 10000000; // 10 KALE
     let expected_bonus = (deposit_amount * 50) / 10000; // 0.5%
@@ -106,36 +103,80 @@ fn test_deposit_with_referral() {
         &user,
         &deposit_amount,
         &RiskProfile::Aggressive,
-        &Some(referrer),
+        &Some(referrer.clone()),
     );
     
     assert_eq!(result.referral_bonus, expected_bonus);
+    assert_eq!(client.get_referral_budget(), 1000000 - expected_bonus);
+    assert_eq!(client.get_referrer(&user), Some(referrer.clone()));
+    
+    let stats = client.get_referral_stats(&referrer);
+    assert_eq!(stats.referred_members, 1);
+    assert_eq!(stats.referred_volume, deposit_amount);
+    assert_eq!(stats.rewards_earned, expected_bonus);
+    
+    // Every member gets a resolvable referral code
+    let code = client.get_account(&user).referral_code;
+    assert_eq!(code, String::from_str(&env, "KPF2"));
+    assert_eq!(client.resolve_referral_code(&code), user);
 }
 
 #[test]
-#[should_panic(expected = "Invalid deposit amount")]
-fn test_deposit_below_minimum() {
+#[should_panic(expected = "Self-referral not allowed")]
+fn test_self_referral_rejected() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, PensionFundContract);
-    let client = PensionFundContractClient::new(&env, &contract_id);
+    let (client, _, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
     
-    let admin = Address::generate(&env);
-    let user = Address::generate(&env);
-    let kale_token = Address::generate(&env);
+    client.deposit(&user, &10000000, &RiskProfile::Moderate, &Some(user.clone()));
+}
+
+#[test]
+fn test_referral_rewards_limited_by_budget() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let referrer = member(&env, &kale_token, 100000000);
+    let user = member(&env, &kale_token, 100000000);
+    token::StellarAssetClient::new(&env, &kale_token).mint(&admin, &100000000);
     
-    client.initialize(
-        &admin,
-        &kale_token,
-        &1000000, // min_deposit: 1 KALE
-        &10000000000,
-        &2592000,
-        &100,
-        &200,
-        &500,
-        &50,
-    );
+    client.deposit(&referrer, &1000000, &RiskProfile::Moderate, &None::<Address>);
+    client.fund_referral_budget(&admin, &20000);
     
-    env.mock_all_auths();
+    let result = client.deposit(&user, &10000000, &RiskProfile::Moderate, &Some(referrer));
+    assert_eq!(result.referral_bonus, 20000);
+    assert_eq!(client.get_referral_budget(), 0);
+}
+
+#[test]
+fn test_referral_tiers_capped_and_descending() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let upline = member(&env, &kale_token, 100000000);
+    let referrer = member(&env, &kale_token, 100000000);
+    let user = member(&env, &kale_token, 100000000);
+    token::StellarAssetClient::new(&env, &kale_token).mint(&admin, &100000000);
+    
+    assert!(client.try_set_referral_tiers(&admin, &vec![&env, 501u32]).is_err());
+    assert!(client.try_set_referral_tiers(&admin, &vec![&env, 10u32, 25u32]).is_err());
+    
+    let change_id = client.set_referral_tiers(&admin, &vec![&env, 25u32]);
+    execute_queued(&env, &client, change_id);
+    
+    client.deposit(&upline, &1000000, &RiskProfile::Moderate, &None::<Address>);
+    client.deposit(&referrer, &1000000, &RiskProfile::Moderate, &Some(upline.clone()));
+    client.fund_referral_budget(&admin, &1000000);
+    
+    let result = client.deposit(&user, &10000000, &RiskProfile::Moderate, &Some(referrer.clone()));
+    assert_eq!(result.referral_bonus, 50000 + 25000);
+    assert_eq!(client.get_referral_stats(&upline).rewards_earned, 25000);
+}
+
+#[test]
+#[should_panic(expected = "Invalid deposit amount")]
+fn test_deposit_below_minimum() {
+    let env = Env::default();
+    let (client, _, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
     
     // Try to deposit below minimum
     client.deposit(
@@ -149,26 +190,8 @@ fn test_deposit_below_minimum() {
 #[test]
 fn test_withdraw_success() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, PensionFundContract);
-    let client = PensionFundContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
-    let user = Address::generate(&env);
-    let kale_token = Address::generate(&env);
-    
-    client.initialize(
-        &admin,
-        &kale_token,
-        &1000000,
-        &10000000000,
-        &2592000,
-        &100, // 1% withdrawal fee
-        &200,
-        &500, // 5% early withdrawal penalty
-        &50,
-    );
-    
-    env.mock_all_auths();
+    let (client, _, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
     
     // First deposit
     let deposit_amount = 10000000; // 10 KALE
@@ -201,26 +224,8 @@ fn test_withdraw_success() {
 #[test]
 fn test_early_withdrawal_penalty() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, PensionFundContract);
-    let client = PensionFundContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
-    let user = Address::generate(&env);
-    let kale_token = Address::generate(&env);
-    
-    client.initialize(
-        &admin,
-        &kale_token,
-        &1000000,
-        &10000000000,
-        &2592000,
-        &100, // 1% withdrawal fee
-        &200,
-        &500, // 5% early withdrawal penalty
-        &50,
-    );
-    
-    env.mock_all_auths();
+    let (client, _, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
     
     // Deposit
     let deposit_amount = 10000000; // 10 KALE
@@ -248,26 +253,8 @@ fn test_early_withdrawal_penalty() {
 #[should_panic(expected = "Insufficient balance")]
 fn test_withdraw_insufficient_balance() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, PensionFundContract);
-    let client = PensionFundContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
+    let (client, _, _) = setup(&env, 2592000);
     let user = Address::generate(&env);
-    let kale_token = Address::generate(&env);
-    
-    client.initialize(
-        &admin,
-        &kale_token,
-        &1000000,
-        &10000000000,
-        &2592000,
-        &100,
-        &200,
-        &500,
-        &50,
-    );
-    
-    env.mock_all_auths();
     
     // Try to withdraw without deposit
     client.withdraw(&user, &1000000);
@@ -276,36 +263,29 @@ fn test_withdraw_insufficient_balance() {
 #[test]
 fn test_update_config() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, PensionFundContract);
-    let client = PensionFundContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
-    let kale_token = Address::generate(&env);
+    let (client, admin, _) = setup(&env, 2592000);
     
-    client.initialize(
-        &admin,
-        &kale_token,
-        &1000000,
-        &10000000000,
-        &2592000,
-        &100,
-        &200,
-        &500,
-        &50,
-    );
-    
-    env.mock_all_auths();
-    
-    // Update configuration
-    client.update_config(
+    // Queue configuration update
+    let change_id = client.update_config(
         &admin,
         &Some(2000000), // new min_deposit
         &Some(20000000000), // new max_deposit
         &Some(150), // new withdrawal_fee
         &None::<u32>, // keep performance_fee
         &Some(600), // new early_withdrawal_penalty
+        &None::<u64>, // keep lock_period
+        &None::<u32>, // keep referral_bonus
     );
     
+    // Nothing changes until the timelock delay has passed
+    assert_eq!(client.get_config().min_deposit, 1000000);
+    assert_eq!(client.get_pending_changes().len(), 1);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = client.get_timelock_delay();
+    });
+    client.execute_change(&change_id);
+    
     let updated_config = client.get_config();
     assert_eq!(updated_config.min_deposit, 2000000);
     assert_eq!(updated_config.max_deposit, 20000000000);
@@ -318,26 +298,8 @@ fn test_update_config() {
 #[should_panic(expected = "Unauthorized")]
 fn test_update_config_unauthorized() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, PensionFundContract);
-    let client = PensionFundContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
+    let (client, _, _) = setup(&env, 2592000);
     let unauthorized_user = Address::generate(&env);
-    let kale_token = Address::generate(&env);
-    
-    client.initialize(
-        &admin,
-        &kale_token,
-        &1000000,
-        &10000000000,
-        &2592000,
-        &100,
-        &200,
-        &500,
-        &50,
-    );
-    
-    env.mock_all_auths();
     
     // Try to update config with unauthorized user
     client.update_config(
@@ -347,33 +309,17 @@ fn test_update_config_unauthorized() {
         &None::<u32>,
         &None::<u32>,
         &None::<u32>,
+        &None::<u64>,
+        &None::<u32>,
     );
 }
 
 #[test]
 fn test_total_locked_tracking() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, PensionFundContract);
-    let client = PensionFundContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
-    let user1 = Address::generate(&env);
-    let user2 = Address::generate(&env);
-    let kale_token = Address::generate(&env);
-    
-    client.initialize(
-        &admin,
-        &kale_token,
-        &1000000,
-        &10000000000,
-        &2592000,
-        &100,
-        &200,
-        &500,
-        &50,
-    );
-    
-    env.mock_all_auths();
+    let (client, _, kale_token) = setup(&env, 2592000);
+    let user1 = member(&env, &kale_token, 100000000);
+    let user2 = member(&env, &kale_token, 100000000);
     
     // Initial total should be 0
     assert_eq!(client.get_total_locked(), 0);
@@ -403,3 +349,1220 @@ fn test_total_locked_tracking() {
     client.withdraw(&user1, &withdraw1);
     assert_eq!(client.get_total_locked(), deposit1 + deposit2 - withdraw1);
 }
+
+#[test]
+fn test_sleeves_track_separate_nav() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let user1 = member(&env, &kale_token, 100000000);
+    let user2 = member(&env, &kale_token, 100000000);
+    
+    client.deposit(&user1, &10000000, &RiskProfile::Conservative, &None::<Address>);
+    client.deposit(&user2, &10000000, &RiskProfile::Aggressive, &None::<Address>);
+    
+    // Aggressive sleeve gains 20%, conservative sleeve is unchanged
+    client.update_sleeve_value(&admin, &RiskProfile::Aggressive, &12000000);
+    
+    assert_eq!(client.get_nav(&RiskProfile::Conservative), 10000000);
+    assert_eq!(client.get_nav(&RiskProfile::Aggressive), 12000000);
+    assert_eq!(client.get_account(&user1).balance, 10000000);
+    assert_eq!(client.get_account(&user2).balance, 12000000);
+    
+    // New aggressive deposits buy in at the higher NAV
+    let user3 = member(&env, &kale_token, 6000000);
+    let result = client.deposit(&user3, &6000000, &RiskProfile::Aggressive, &None::<Address>);
+    assert_eq!(result.shares_issued, 5000000);
+    
+    let sleeve = client.get_sleeve(&RiskProfile::Aggressive);
+    assert_eq!(sleeve.total_shares, 15000000);
+    assert_eq!(sleeve.total_value, 18000000);
+}
+
+#[test]
+#[should_panic(expected = "Risk profile mismatch")]
+fn test_deposit_into_other_sleeve_rejected() {
+    let env = Env::default();
+    let (client, _, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
+    
+    client.deposit(&user, &5000000, &RiskProfile::Moderate, &None::<Address>);
+    client.deposit(&user, &5000000, &RiskProfile::Aggressive, &None::<Address>);
+}
+
+#[test]
+fn test_change_risk_profile_moves_value_at_nav() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
+    
+    assert!(client.try_set_profile_switch_policy(&admin, &86400, &1000).is_err());
+    let change_id = client.set_profile_switch_policy(&admin, &86400, &100); // 1 day, 1% fee
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = client.get_timelock_delay();
+    });
+    client.execute_change(&change_id);
+    client.deposit(&user, &10000000, &RiskProfile::Conservative, &None::<Address>);
+    
+    let change = client.change_risk_profile(&user, &RiskProfile::Aggressive);
+    assert_eq!(change.value_moved, 10000000);
+    assert_eq!(change.fee, 100000);
+    
+    let account = client.get_account(&user);
+    assert_eq!(account.risk_profile, RiskProfile::Aggressive);
+    assert_eq!(account.balance, 9900000);
+    assert_eq!(client.get_sleeve(&RiskProfile::Aggressive).total_value, 9900000);
+    assert_eq!(client.get_sleeve(&RiskProfile::Conservative).total_value, 0);
+    assert_eq!(client.get_profile_history(&user).len(), 1);
+    
+    // The switching fee is booked to the treasury
+    assert_eq!(client.get_treasury_ledger().switching_fees, 100000);
+    assert_eq!(client.get_total_locked(), 9900000);
+    assert_eq!(client.reconcile().surplus, 0);
+}
+
+#[test]
+#[should_panic(expected = "Profile change cooldown active")]
+fn test_change_risk_profile_cooldown() {
+    let env = Env::default();
+    let (client, _, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
+    
+    client.deposit(&user, &10000000, &RiskProfile::Conservative, &None::<Address>);
+    client.change_risk_profile(&user, &RiskProfile::Moderate);
+    client.change_risk_profile(&user, &RiskProfile::Aggressive);
+}
+
+#[test]
+fn test_withdraw_consumes_unlocked_tranches_first() {
+    let env = Env::default();
+    let (client, _, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
+    
+    client.deposit(&user, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    
+    // A top-up later on does not re-lock the first deposit
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 1000000;
+    });
+    client.deposit(&user, &5000000, &RiskProfile::Moderate, &None::<Address>);
+    assert_eq!(client.get_tranches(&user).len(), 2);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 2592001;
+    });
+    
+    // 10 KALE comes from the unlocked tranche, 2 KALE from the locked one
+    let result = client.withdraw(&user, &12000000);
+    assert_eq!(result.fee, 120000);
+    assert_eq!(result.penalty, 100000);
+    
+    let tranches = client.get_tranches(&user);
+    assert_eq!(tranches.len(), 1);
+    assert_eq!(tranches.get(0).unwrap().shares, 3000000);
+    assert_eq!(tranches.get(0).unwrap().unlock_at, 1000000 + 2592000);
+}
+
+#[test]
+fn test_retirement_monthly_payouts() {
+    let env = Env::default();
+    let (client, _, kale_token) = setup(&env, 31536000);
+    let user = member(&env, &kale_token, 100000000);
+    
+    client.deposit(&user, &10000000, &RiskProfile::Conservative, &None::<Address>);
+    
+    // Retiring cannot cut the lock period short
+    assert!(client
+        .try_register_retirement(&user, &1000000, &PayoutPlan::FixedMonthly(4000000))
+        .is_err());
+    client.register_retirement(&user, &31536000, &PayoutPlan::FixedMonthly(4000000));
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 31536000;
+    });
+    
+    let first = client.process_payout(&user);
+    assert_eq!(first.amount, 4000000);
+    assert_eq!(first.penalty, 0);
+    assert_eq!(first.fee, 40000);
+    
+    let plan = client.get_retirement_plan(&user).unwrap();
+    assert_eq!(plan.payouts_made, 1);
+    assert_eq!(plan.next_payout, 31536000 + 2592000);
+    
+    // A top-up made in retirement stays locked; once the older 6 KALE are
+    // used up, 2 KALE of the third installment pay the penalty
+    client.deposit(&user, &10000000, &RiskProfile::Conservative, &None::<Address>);
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 31536000 + 2592000;
+    });
+    assert_eq!(client.process_payout(&user).penalty, 0);
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 31536000 + 2 * 2592000;
+    });
+    assert_eq!(client.process_payout(&user).penalty, 100000);
+}
+
+#[test]
+#[should_panic(expected = "Payout not due")]
+fn test_retirement_payout_before_date() {
+    let env = Env::default();
+    let (client, _, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
+    
+    client.deposit(&user, &10000000, &RiskProfile::Conservative, &None::<Address>);
+    client.register_retirement(&user, &2592000, &PayoutPlan::LumpSum);
+    client.process_payout(&user);
+}
+
+#[test]
+fn test_inheritance_claim_cancelled_by_heartbeat() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let owner = member(&env, &kale_token, 100000000);
+    let heir1 = Address::generate(&env);
+    let heir2 = Address::generate(&env);
+    
+    assert!(client.try_set_inheritance_policy(&admin, &0, &500).is_err());
+    assert!(client.try_set_inheritance_policy(&admin, &1000, &0).is_err());
+    let change_id = client.set_inheritance_policy(&admin, &1000, &500);
+    execute_queued(&env, &client, change_id);
+    
+    let start = env.ledger().timestamp();
+    client.deposit(&owner, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    
+    let mut beneficiaries = Vec::new(&env);
+    beneficiaries.push_back(Beneficiary { address: heir1.clone(), share: 6000 });
+    beneficiaries.push_back(Beneficiary { address: heir2.clone(), share: 4000 });
+    client.set_beneficiaries(&owner, &beneficiaries);
+    
+    let keeper = Address::generate(&env);
+    client.set_keeper(&admin, &keeper);
+    token::Client::new(&env, &kale_token).approve(&owner, &client.address, &10000000, &1000);
+    client.set_contribution_schedule(&owner, &10000000, &2592000, &RiskProfile::Moderate, &0, &31536000);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = start + 1000;
+    });
+    client.initiate_inheritance_claim(&heir1, &owner);
+    
+    // A keeper collecting a contribution is not the owner checking in
+    assert!(client.execute_contribution(&keeper, &owner).is_some());
+    assert!(client.get_inheritance_claim(&owner).is_some());
+    
+    // Owner checks in before the claim delay runs out
+    client.heartbeat(&owner);
+    assert!(client.get_inheritance_claim(&owner).is_none());
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = start + 2000;
+    });
+    let claim = client.initiate_inheritance_claim(&heir2, &owner);
+    assert_eq!(claim.executable_at, start + 2500);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = start + 2500;
+    });
+    let result = client.execute_inheritance_claim(&owner);
+    assert_eq!(result.amount, 20000000);
+    assert_eq!(result.penalty, 0);
+    assert!(client.get_inheritance_claim(&owner).is_none());
+}
+
+#[test]
+fn test_performance_fee_above_high_water_mark() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
+    
+    client.deposit(&user, &10000000, &RiskProfile::Aggressive, &None::<Address>);
+    client.update_sleeve_value(&admin, &RiskProfile::Aggressive, &12000000);
+    
+    // 2% of the 2 KALE gain goes to the treasury
+    let accrual = client.crystallize_performance_fee(&RiskProfile::Aggressive);
+    assert_eq!(accrual.performance_fees, 40000);
+    assert!(accrual.fee_shares > 0);
+    assert_eq!(client.get_account(&user).balance, 11960000);
+    
+    // A drawdown followed by recovery to the high-water mark charges nothing
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 7776000;
+    });
+    client.update_sleeve_value(&admin, &RiskProfile::Aggressive, &11000000);
+    let accrual = client.crystallize_performance_fee(&RiskProfile::Aggressive);
+    assert_eq!(accrual.performance_fees, 40000);
+    
+    // Fee shares belong to the treasury address and pay out to it
+    let treasury = Address::generate(&env);
+    let change_id = client.set_treasury(&admin, &treasury);
+    execute_queued(&env, &client, change_id);
+    let value = client.redeem_fee_shares(&RiskProfile::Aggressive, &accrual.fee_shares);
+    assert!(value > 0);
+    assert_eq!(token::Client::new(&env, &kale_token).balance(&treasury), value);
+    assert_eq!(client.get_fee_accruals().get(2).unwrap().fee_shares, 0);
+}
+
+#[test]
+fn test_management_fee_accrues_over_time() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
+    
+    let change_id = client.set_management_fee(&admin, &100); // 1% per year
+    let start = client.get_timelock_delay();
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = start;
+    });
+    client.execute_change(&change_id);
+    client.deposit(&user, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = start + 31536000; // 1 year
+    });
+    
+    let accruals = client.collect_fees();
+    let moderate = accruals.get(1).unwrap();
+    assert_eq!(moderate.management_fees, 100000);
+    assert_eq!(client.get_account(&user).balance, 9900000);
+}
+
+#[test]
+fn test_full_exit_with_management_fee_pending() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let alice = member(&env, &kale_token, 10000000);
+    let bob = member(&env, &kale_token, 10000000);
+    let carol = member(&env, &kale_token, 10000000);
+    
+    let change_id = client.set_management_fee(&admin, &100); // 1% per year
+    execute_queued(&env, &client, change_id);
+    client.deposit(&alice, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    client.deposit(&bob, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    client.deposit(&carol, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    client.register_retirement(&bob, &31536000, &PayoutPlan::LumpSum);
+    client.register_retirement(&carol, &31536000, &PayoutPlan::Drawdown(10000));
+    
+    // The fee accrued since the last sleeve update is already out of the balance shown
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp += 31536000;
+    });
+    let balance = client.get_account(&alice).balance;
+    assert!(balance < 10000000);
+    assert_eq!(client.withdraw(&alice, &balance).amount, balance);
+    assert_eq!(client.get_account(&alice).shares, 0);
+    
+    client.process_payout(&bob);
+    assert_eq!(client.get_account(&bob).shares, 0);
+    client.process_payout(&carol);
+    assert_eq!(client.get_account(&carol).shares, 0);
+}
+
+#[test]
+#[should_panic(expected = "Management fee above cap")]
+fn test_management_fee_cap() {
+    let env = Env::default();
+    let (client, admin, _) = setup(&env, 2592000);
+    
+    client.set_management_fee(&admin, &500);
+}
+
+#[test]
+fn test_treasury_records_fees_and_penalties() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
+    
+    client.deposit(&user, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    client.withdraw(&user, &5000000);
+    
+    let ledger = client.get_treasury_ledger();
+    assert_eq!(ledger.withdrawal_fees, 50000);
+    assert_eq!(ledger.early_withdrawal_penalties, 250000);
+    assert_eq!(client.get_treasury_balance(), 300000);
+    
+    let treasury = Address::generate(&env);
+    client.withdraw_treasury(&admin, &treasury, &100000);
+    assert_eq!(client.get_treasury_balance(), 200000);
+}
+
+#[test]
+fn test_penalty_redistribution_to_remaining_members() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let leaver = member(&env, &kale_token, 100000000);
+    let saver = member(&env, &kale_token, 100000000);
+    
+    let change_id = client.set_penalty_redistribution(&admin, &true);
+    execute_queued(&env, &client, change_id);
+    client.deposit(&leaver, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    client.deposit(&saver, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    
+    client.withdraw(&leaver, &10000000);
+    
+    // The 0.5 KALE penalty stays with the member who kept saving
+    assert_eq!(client.get_treasury_ledger().early_withdrawal_penalties, 0);
+    assert_eq!(client.get_account(&saver).balance, 10500000);
+}
+
+#[test]
+fn test_claim_and_deposit_with_referral_code() {
+    let env = Env::default();
+    let (client, _, kale_token) = setup(&env, 2592000);
+    let referrer = member(&env, &kale_token, 100000000);
+    let user = member(&env, &kale_token, 100000000);
+    
+    client.deposit(&referrer, &1000000, &RiskProfile::Moderate, &None::<Address>);
+    let default_code = client.get_account(&referrer).referral_code;
+    
+    let code = String::from_str(&env, "KALEFARM");
+    client.claim_referral_code(&referrer, &code);
+    assert_eq!(client.resolve_referral_code(&code), referrer);
+    assert!(client.try_resolve_referral_code(&default_code).is_err());
+    
+    client.deposit_with_code(&user, &5000000, &RiskProfile::Moderate, &code);
+    assert_eq!(client.get_referrer(&user), Some(referrer));
+}
+
+#[test]
+#[should_panic(expected = "Referral code taken")]
+fn test_referral_code_must_be_unique() {
+    let env = Env::default();
+    let (client, _, kale_token) = setup(&env, 2592000);
+    let user1 = member(&env, &kale_token, 100000000);
+    let user2 = member(&env, &kale_token, 100000000);
+    
+    client.deposit(&user1, &1000000, &RiskProfile::Moderate, &None::<Address>);
+    client.deposit(&user2, &1000000, &RiskProfile::Moderate, &None::<Address>);
+    
+    let code = String::from_str(&env, "SAVER1");
+    client.claim_referral_code(&user1, &code);
+    client.claim_referral_code(&user2, &code);
+}
+
+#[test]
+#[should_panic(expected = "Invalid deposit limits")]
+fn test_initialize_rejects_min_above_max() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, PensionFundContract);
+    let client = PensionFundContractClient::new(&env, &contract_id);
+    
+    let admin = Address::generate(&env);
+    let kale_token = env.register_stellar_asset_contract_v2(admin.clone()).address();
+    
+    client.initialize(
+        &admin,
+        &kale_token,
+        &10000000000, // min_deposit above max_deposit
+        &1000000,
+        &2592000,
+        &100,
+        &200,
+        &500,
+        &50,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Withdrawal fee above cap")]
+fn test_update_config_rejects_excessive_fee() {
+    let env = Env::default();
+    let (client, admin, _) = setup(&env, 2592000);
+    
+    client.update_config(
+        &admin,
+        &None::<i128>,
+        &None::<i128>,
+        &Some(20000), // 200% would make net_amount negative
+        &None::<u32>,
+        &None::<u32>,
+        &None::<u64>,
+        &None::<u32>,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Timelock not expired")]
+fn test_queued_change_cannot_execute_early() {
+    let env = Env::default();
+    let (client, admin, _) = setup(&env, 2592000);
+    
+    let change_id = client.update_config(
+        &admin,
+        &None::<i128>,
+        &None::<i128>,
+        &Some(150),
+        &None::<u32>,
+        &None::<u32>,
+        &None::<u64>,
+        &None::<u32>,
+    );
+    client.execute_change(&change_id);
+}
+
+#[test]
+fn test_guardian_cancels_queued_change() {
+    let env = Env::default();
+    let (client, admin, _) = setup(&env, 2592000);
+    let guardian = Address::generate(&env);
+    
+    client.set_guardian(&admin, &guardian);
+    let change_id = client.set_management_fee(&admin, &150);
+    client.cancel_change(&guardian, &change_id);
+    
+    assert_eq!(client.get_pending_changes().len(), 0);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = client.get_timelock_delay();
+    });
+    assert!(client.try_execute_change(&change_id).is_err());
+    assert_eq!(client.get_config().management_fee, 0);
+}
+
+#[test]
+fn test_member_vote_queues_fee_change() {
+    let env = Env::default();
+    let (client, _, kale_token) = setup(&env, 2592000);
+    let alice = member(&env, &kale_token, 100000000);
+    let bob = member(&env, &kale_token, 100000000);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.sequence_number = 10;
+    });
+    client.deposit(&alice, &30000000, &RiskProfile::Moderate, &None::<Address>);
+    client.deposit(&bob, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.sequence_number = 11;
+    });
+    let proposal_id = client.propose(
+        &alice,
+        &ProposalAction::UpdateFees(FeeUpdate {
+            withdrawal_fee: Some(50),
+            performance_fee: None,
+            early_withdrawal_penalty: None,
+            management_fee: None,
+        }),
+    );
+    
+    assert_eq!(client.vote(&alice, &proposal_id, &true), 30000000);
+    assert_eq!(client.vote(&bob, &proposal_id, &false), 10000000);
+    assert!(client.try_vote(&bob, &proposal_id, &true).is_err());
+    
+    let proposal = client.get_proposal(&proposal_id);
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = proposal.end_time;
+    });
+    assert_eq!(client.execute_proposal(&proposal_id), ProposalStatus::Queued);
+    
+    let change_id = client.get_proposal(&proposal_id).queued_change;
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = proposal.end_time + client.get_timelock_delay();
+    });
+    client.execute_change(&change_id);
+    assert_eq!(client.get_config().withdrawal_fee, 50);
+}
+
+#[test]
+fn test_voting_power_uses_snapshot() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let alice = member(&env, &kale_token, 100000000);
+    let latecomer = member(&env, &kale_token, 100000000);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.sequence_number = 10;
+    });
+    client.deposit(&alice, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.sequence_number = 11;
+    });
+    let proposal_id = client.propose(
+        &alice,
+        &ProposalAction::UpdateFees(FeeUpdate {
+            withdrawal_fee: None,
+            performance_fee: Some(1500),
+            early_withdrawal_penalty: None,
+            management_fee: None,
+        }),
+    );
+    
+    // Balances recorded after the snapshot carry no weight
+    client.deposit(&latecomer, &50000000, &RiskProfile::Moderate, &None::<Address>);
+    assert!(client.try_vote(&latecomer, &proposal_id, &false).is_err());
+    assert_eq!(client.get_voting_power(&latecomer, &11), 50000000);
+    assert_eq!(client.get_proposal(&proposal_id).total_weight, 10000000);
+    
+    // Members and the total both count invested principal, so NAV moves change neither
+    client.update_sleeve_value(&admin, &RiskProfile::Moderate, &120000000);
+    env.ledger().with_mut(|ledger| {
+        ledger.sequence_number = 12;
+    });
+    let second_id = client.propose(
+        &alice,
+        &ProposalAction::UpdateFees(FeeUpdate {
+            withdrawal_fee: None,
+            performance_fee: Some(1000),
+            early_withdrawal_penalty: None,
+            management_fee: None,
+        }),
+    );
+    assert_eq!(client.get_account(&alice).balance, 20000000);
+    assert_eq!(client.get_voting_power(&alice, &12), 10000000);
+    assert_eq!(client.get_proposal(&second_id).total_weight, 60000000);
+}
+
+#[test]
+fn test_multisig_admin_action_needs_approvals() {
+    let env = Env::default();
+    let (client, admin, _) = setup(&env, 2592000);
+    let signer_a = Address::generate(&env);
+    let signer_b = Address::generate(&env);
+    let signer_c = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    
+    let mut signers = Vec::new(&env);
+    signers.push_back(signer_a.clone());
+    signers.push_back(signer_b.clone());
+    signers.push_back(signer_c.clone());
+    client.set_signers(&admin, &signers, &2);
+    
+    let action_hash: BytesN<32> = env
+        .crypto()
+        .sha256(&(Symbol::new(&env, "set_treasury"), treasury.clone()).to_xdr(&env))
+        .into();
+    
+    client.approve_action(&signer_a, &action_hash);
+    assert!(client.try_set_treasury(&admin, &treasury).is_err());
+    
+    assert_eq!(client.approve_action(&signer_b, &action_hash), 2);
+    let change_id = client.set_treasury(&signer_c, &treasury);
+    execute_queued(&env, &client, change_id);
+    assert_eq!(client.get_treasury(), treasury);
+    
+    // Approvals are consumed by the action
+    assert_eq!(client.get_approvals(&action_hash).len(), 0);
+}
+
+#[contract]
+struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn set_price(env: Env, asset: Symbol, price_usd: i128) {
+        env.storage().instance().set(&asset, &price_usd);
+    }
+    
+    pub fn get_fresh_price(env: Env, asset: Symbol) -> Option<PriceFeed> {
+        let price_usd: Option<i128> = env.storage().instance().get(&asset);
+        price_usd.map(|price_usd| PriceFeed {
+            asset,
+            price_usd,
+            price_xlm: None,
+            timestamp: env.ledger().timestamp(),
+            confidence: 10000,
+            source: symbol_short!("MOCK"),
+        })
+    }
+}
+
+#[test]
+fn test_deposit_and_withdraw_in_other_asset() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 100000000);
+    
+    let oracle_id = env.register_contract(None, MockOracle);
+    let oracle = MockOracleClient::new(&env, &oracle_id);
+    oracle.set_price(&symbol_short!("KALE"), &2000000); // $0.20
+    oracle.set_price(&symbol_short!("USDC"), &10000000); // $1.00
+    let change_id = client.set_oracle(&admin, &oracle_id);
+    execute_queued(&env, &client, change_id);
+    
+    let usdc = env.register_stellar_asset_contract_v2(admin.clone()).address();
+    token::StellarAssetClient::new(&env, &usdc).mint(&user, &10000000);
+    let change_id = client.register_deposit_asset(&admin, &symbol_short!("USDC"), &usdc, &100); // 1% spread
+    execute_queued(&env, &client, change_id);
+    
+    // 1 USDC is worth 5 KALE, less the 1% spread
+    let deposit = client.deposit_asset(
+        &user,
+        &symbol_short!("USDC"),
+        &10000000,
+        &RiskProfile::Moderate,
+        &None::<Address>,
+    );
+    assert_eq!(deposit.amount, 49500000);
+    // The spread is booked to the treasury in the asset
+    let held = client.get_deposit_asset(&symbol_short!("USDC"));
+    assert_eq!(held.balance, 9900000);
+    assert_eq!(held.treasury, 100000);
+    
+    // No KALE is owed, but the member's shares still block a token migration
+    assert_eq!(client.reconcile().expected_balance, 0);
+    let change_id = client.migrate_kale_token(&admin, &Address::generate(&env));
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp += client.get_timelock_delay();
+    });
+    assert!(client.try_execute_change(&change_id).is_err());
+    
+    let result = client.withdraw_asset(&user, &symbol_short!("USDC"), &10000000);
+    let usdc_client = token::Client::new(&env, &usdc);
+    assert_eq!(usdc_client.balance(&user), result.asset_amount);
+    let gross_amount = result.withdrawal.net_amount * 2000000 / 10000000;
+    assert_eq!(
+        result.asset_amount,
+        result.withdrawal.net_amount * 2000000 * 9900 / (10000000 * 10000)
+    );
+    let held = client.get_deposit_asset(&symbol_short!("USDC"));
+    assert_eq!(held.balance, 9900000 - gross_amount);
+    assert_eq!(held.treasury, 100000 + gross_amount - result.asset_amount);
+    
+    let treasury = Address::generate(&env);
+    client.withdraw_asset_treasury(&admin, &symbol_short!("USDC"), &treasury, &held.treasury);
+    assert_eq!(usdc_client.balance(&treasury), held.treasury);
+    assert_eq!(client.get_deposit_asset(&symbol_short!("USDC")).treasury, 0);
+}
+
+#[test]
+fn test_withdrawal_queue_settles_after_notice() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 0);
+    let keeper = Address::generate(&env);
+    let user = member(&env, &kale_token, 20000000);
+    
+    client.set_keeper(&admin, &keeper);
+    client.deposit(&user, &20000000, &RiskProfile::Moderate, &None::<Address>);
+    
+    let request = client.request_withdrawal(&user, &10000000);
+    assert_eq!(request.epoch, 1);
+    
+    // Queued shares cannot leave through an instant withdrawal
+    assert!(client.try_withdraw(&user, &15000000).is_err());
+    
+    // Requests still inside their notice period roll into the next epoch
+    let settlement = client.settle_withdrawal_epoch(&keeper);
+    assert_eq!(settlement.requests_settled, 0);
+    assert_eq!(client.get_withdrawal_request(&request.id).epoch, 2);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = client.get_withdrawal_notice();
+    });
+    let settlement = client.settle_withdrawal_epoch(&keeper);
+    assert_eq!(settlement.epoch, 2);
+    assert_eq!(settlement.requests_settled, 1);
+    assert_eq!(client.get_queued_shares(&user), 0);
+    
+    let result = client.claim_withdrawal(&user, &request.id);
+    assert_eq!(result.amount, 10000000);
+    assert_eq!(token::Client::new(&env, &kale_token).balance(&user), result.net_amount);
+    assert_eq!(client.get_claims_reserved(), 0);
+    assert!(client.try_claim_withdrawal(&user, &request.id).is_err());
+}
+
+#[test]
+fn test_withdrawal_settlement_carries_unredeemable_requests_in_pages() {
+    let env = Env::default();
+    env.budget().reset_unlimited();
+    let (client, admin, kale_token) = setup(&env, 0);
+    let alice = member(&env, &kale_token, 60000000);
+    let bob = member(&env, &kale_token, 10000000);
+    
+    client.deposit(&alice, &60000000, &RiskProfile::Moderate, &None::<Address>);
+    client.deposit(&bob, &10000000, &RiskProfile::Aggressive, &None::<Address>);
+    
+    // Requests below the minimum deposit must take all free shares
+    assert!(client.try_request_withdrawal(&alice, &100).is_err());
+    
+    let bob_request = client.request_withdrawal(&bob, &10000000);
+    for _ in 0..50 {
+        client.request_withdrawal(&alice, &1000000);
+    }
+    
+    // A sleeve written down to nothing cannot be redeemed, so its requests wait
+    client.update_sleeve_value(&admin, &RiskProfile::Aggressive, &0);
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = client.get_withdrawal_notice();
+    });
+    
+    let settlement = client.settle_withdrawal_epoch(&admin);
+    assert_eq!(settlement.requests_settled, 49);
+    assert!(!settlement.complete);
+    assert_eq!(client.get_withdrawal_epoch(), 1);
+    assert_eq!(client.get_withdrawal_request(&bob_request.id).epoch, 2);
+    
+    // A revaluation between pages does not change the epoch's NAV
+    client.update_sleeve_value(&admin, &RiskProfile::Moderate, &22000000);
+    let settlement = client.settle_withdrawal_epoch(&admin);
+    assert_eq!(settlement.epoch, 1);
+    assert_eq!(settlement.requests_settled, 50);
+    assert_eq!(settlement.total_payout, 50 * 990000);
+    assert!(settlement.complete);
+    assert_eq!(client.get_withdrawal_epoch(), 2);
+    assert_eq!(client.get_queued_shares(&alice), 0);
+    assert_eq!(client.get_queued_shares(&bob), 10000000);
+}
+
+#[test]
+fn test_withdrawal_settlement_keeps_treasury_reserve() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 0);
+    let alice = member(&env, &kale_token, 10000000);
+    let bob = member(&env, &kale_token, 10000000);
+    
+    client.deposit(&alice, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    client.deposit(&bob, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    client.withdraw(&bob, &10000000);
+    client.request_withdrawal(&alice, &10000000);
+    
+    // Marked up above the KALE held, the payout would eat into the treasury's fees
+    client.update_sleeve_value(&admin, &RiskProfile::Moderate, &10050000);
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = client.get_withdrawal_notice();
+    });
+    assert!(client.try_settle_withdrawal_epoch(&admin).is_err());
+    
+    client.update_sleeve_value(&admin, &RiskProfile::Moderate, &10000000);
+    client.settle_withdrawal_epoch(&admin);
+    assert_eq!(client.reconcile().surplus, 0);
+    assert_eq!(client.reconcile().member_funds, 0);
+}
+
+#[test]
+fn test_hardship_waiver_capped_by_annual_limit() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let approver = Address::generate(&env);
+    let user = member(&env, &kale_token, 20000000);
+    
+    client.set_hardship_approver(&admin, &approver, &true);
+    let change_id = client.set_hardship_limit(&admin, &300000);
+    execute_queued(&env, &client, change_id);
+    client.deposit(&user, &20000000, &RiskProfile::Moderate, &None::<Address>);
+    
+    let request_id = client.submit_hardship_request(&user, &10000000, &HardshipReason::Medical);
+    assert!(client.try_execute_hardship_withdrawal(&user, &request_id).is_err());
+    assert!(client.try_decide_hardship_request(&user, &request_id, &true, &10000).is_err());
+    
+    client.decide_hardship_request(&approver, &request_id, &true, &10000);
+    
+    // Full 5% penalty is 500000 KALE; only 300000 of it can be waived this year
+    let result = client.execute_hardship_withdrawal(&user, &request_id);
+    assert_eq!(result.penalty_waived, 300000);
+    assert_eq!(result.penalty, 200000);
+    assert_eq!(result.net_amount, 9700000);
+    assert_eq!(client.get_hardship_usage(&user).waived, 300000);
+    
+    let request = client.get_hardship_request(&request_id);
+    assert_eq!(request.status, HardshipStatus::Executed);
+    assert_eq!(request.approver, Some(approver));
+}
+
+#[test]
+fn test_recurring_contributions_track_missed_periods() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let keeper = Address::generate(&env);
+    let user = member(&env, &kale_token, 30000000);
+    token::Client::new(&env, &kale_token).approve(&user, &client.address, &20000000, &1000);
+    
+    client.set_keeper(&admin, &keeper);
+    client.set_contribution_schedule(&user, &10000000, &2592000, &RiskProfile::Moderate, &0, &31536000);
+    
+    assert!(client.execute_contribution(&keeper, &user).is_some());
+    assert!(client.try_execute_contribution(&keeper, &user).is_err());
+    
+    // Two periods pass uncollected before the keeper runs again
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 3 * 2592000;
+    });
+    assert!(client.execute_contribution(&keeper, &user).is_some());
+    
+    // The allowance is used up, so the next contribution is missed
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 4 * 2592000;
+    });
+    assert!(client.execute_contribution(&keeper, &user).is_none());
+    
+    let schedule = client.get_contribution_schedule(&user).unwrap();
+    assert_eq!(schedule.contributions_made, 2);
+    assert_eq!(schedule.contributions_missed, 3);
+    assert_eq!(schedule.total_contributed, 20000000);
+    assert_eq!(schedule.next_due, 5 * 2592000);
+    
+    client.pause_contributions(&user);
+    assert!(client.try_execute_contribution(&keeper, &user).is_err());
+    client.cancel_contributions(&user);
+    assert!(client.get_contribution_schedule(&user).is_none());
+}
+
+#[test]
+fn test_employer_match_forfeited_before_cliff() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 0);
+    let employer = member(&env, &kale_token, 100000000);
+    let employee = Address::generate(&env);
+    
+    client.register_employer(&admin, &employer, &5000, &31536000); // 50% match, 1 year cliff
+    client.fund_match_budget(&employer, &10000000);
+    client.enroll_employee(&employer, &employee);
+    
+    let mut contributions = Vec::new(&env);
+    contributions.push_back(EmployeeContribution {
+        employee: employee.clone(),
+        amount: 10000000,
+    });
+    let deposits = client.employer_deposit(&employer, &contributions);
+    assert_eq!(deposits.get(0).unwrap().matched, 5000000);
+    assert_eq!(client.get_account(&employee).balance, 15000000);
+    
+    // The unvested match cannot be withdrawn
+    assert!(client.try_withdraw(&employee, &12000000).is_err());
+    
+    assert_eq!(client.terminate_employee(&employer, &employee), 5000000);
+    assert_eq!(client.get_account(&employee).balance, 10000000);
+    assert_eq!(token::Client::new(&env, &kale_token).balance(&employer), 85000000);
+    assert_eq!(client.get_employer(&employer).match_budget, 5000000);
+}
+
+#[test]
+fn test_forfeiture_burns_only_match_tranches() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let employer = member(&env, &kale_token, 100000000);
+    let employee = Address::generate(&env);
+    
+    client.register_employer(&admin, &employer, &5000, &31536000); // 50% match, 1 year cliff
+    client.fund_match_budget(&employer, &10000000);
+    client.enroll_employee(&employer, &employee);
+    let mut contributions = Vec::new(&env);
+    contributions.push_back(EmployeeContribution {
+        employee: employee.clone(),
+        amount: 10000000,
+    });
+    client.employer_deposit(&employer, &contributions);
+    
+    // The second contribution lands after the first has unlocked
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 2678400;
+    });
+    client.employer_deposit(&employer, &contributions);
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 3456000;
+    });
+    
+    assert_eq!(client.terminate_employee(&employer, &employee), 10000000);
+    let tranches = client.get_tranches(&employee);
+    assert_eq!(tranches.len(), 2);
+    assert_eq!(tranches.get(0).unwrap().principal + tranches.get(1).unwrap().principal, 20000000);
+    assert_eq!(tranches.get(0).unwrap().vests_at, 0);
+    
+    // The employee's own unlocked contribution leaves without a penalty
+    let result = client.withdraw(&employee, &10000000);
+    assert_eq!(result.penalty, 0);
+}
+
+#[test]
+fn test_inheritance_and_lump_sum_skip_held_shares() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 0);
+    let employer = member(&env, &kale_token, 100000000);
+    let employee = Address::generate(&env);
+    let retiree = member(&env, &kale_token, 10000000);
+    let heir = Address::generate(&env);
+    
+    let change_id = client.set_inheritance_policy(&admin, &1000, &500);
+    execute_queued(&env, &client, change_id);
+    let start = env.ledger().timestamp();
+    
+    client.register_employer(&admin, &employer, &5000, &31536000); // 50% match, 1 year cliff
+    client.fund_match_budget(&employer, &10000000);
+    client.enroll_employee(&employer, &employee);
+    let mut contributions = Vec::new(&env);
+    contributions.push_back(EmployeeContribution {
+        employee: employee.clone(),
+        amount: 10000000,
+    });
+    client.employer_deposit(&employer, &contributions);
+    
+    let mut beneficiaries = Vec::new(&env);
+    beneficiaries.push_back(Beneficiary { address: heir.clone(), share: 10000 });
+    client.set_beneficiaries(&employee, &beneficiaries);
+    client.request_withdrawal(&employee, &2000000);
+    
+    // A lump sum pays the free shares and leaves the queued request to settle
+    client.deposit(&retiree, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    client.request_withdrawal(&retiree, &4000000);
+    client.register_retirement(&retiree, &(start + 100), &PayoutPlan::LumpSum);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = start + 1000;
+    });
+    assert_eq!(client.process_payout(&retiree).amount, 6000000);
+    assert_eq!(client.get_queued_shares(&retiree), 4000000);
+    
+    // The claim cancels the queued request and forfeits the unvested match
+    client.initiate_inheritance_claim(&heir, &employee);
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = start + 1500;
+    });
+    let result = client.execute_inheritance_claim(&employee);
+    assert_eq!(result.amount, 10000000);
+    assert_eq!(token::Client::new(&env, &kale_token).balance(&heir), result.net_amount);
+    assert_eq!(client.get_queued_shares(&employee), 0);
+    assert_eq!(client.get_withdrawal_requests(&employee).len(), 0);
+    assert_eq!(client.get_employer(&employer).total_forfeited, 5000000);
+    assert_eq!(client.get_account(&employee).shares, 0);
+}
+
+#[test]
+fn test_batch_deposit_reports_failed_items() {
+    let env = Env::default();
+    let (client, _, kale_token) = setup(&env, 0);
+    let alice = member(&env, &kale_token, 10000000);
+    let bob = member(&env, &kale_token, 1000000);
+    let carol = member(&env, &kale_token, 10000000);
+    
+    let mut deposits = Vec::new(&env);
+    deposits.push_back(DepositRequest {
+        user: alice.clone(),
+        amount: 10000000,
+        risk_profile: RiskProfile::Moderate,
+    });
+    deposits.push_back(DepositRequest {
+        user: bob.clone(),
+        amount: 5000000,
+        risk_profile: RiskProfile::Moderate,
+    });
+    deposits.push_back(DepositRequest {
+        user: carol.clone(),
+        amount: 100,
+        risk_profile: RiskProfile::Aggressive,
+    });
+    
+    let results = client.batch_deposit(&deposits);
+    assert!(matches!(results.get(0).unwrap().status, BatchDepositStatus::Deposited(_)));
+    assert!(matches!(
+        results.get(1).unwrap().status,
+        BatchDepositStatus::Failed(BatchStatus::InsufficientFunds)
+    ));
+    assert!(matches!(
+        results.get(2).unwrap().status,
+        BatchDepositStatus::Failed(BatchStatus::InvalidAmount)
+    ));
+    assert_eq!(client.get_total_locked(), 10000000);
+    assert_eq!(client.get_account(&alice).balance, 10000000);
+}
+
+#[test]
+fn test_batch_process_withdrawals_pays_settled_requests() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 0);
+    let alice = member(&env, &kale_token, 10000000);
+    let bob = Address::generate(&env);
+    
+    client.deposit(&alice, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    client.request_withdrawal(&alice, &4000000);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = client.get_withdrawal_notice();
+    });
+    client.settle_withdrawal_epoch(&admin);
+    
+    let mut users = Vec::new(&env);
+    users.push_back(alice.clone());
+    users.push_back(bob.clone());
+    let results = client.batch_process_withdrawals(&users);
+    
+    let paid = results.get(0).unwrap();
+    assert_eq!(paid.status, BatchStatus::Success);
+    assert_eq!(paid.requests_paid, 1);
+    assert_eq!(token::Client::new(&env, &kale_token).balance(&alice), paid.amount_paid);
+    assert_eq!(results.get(1).unwrap().status, BatchStatus::NothingToClaim);
+    assert_eq!(client.get_withdrawal_requests(&alice).len(), 0);
+    assert_eq!(client.get_claims_reserved(), 0);
+}
+
+#[test]
+fn test_statement_filters_and_paginates_activity() {
+    let env = Env::default();
+    let (client, _, kale_token) = setup(&env, 0);
+    let user = member(&env, &kale_token, 20000000);
+    
+    client.deposit(&user, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 100;
+    });
+    client.withdraw(&user, &2000000);
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 200;
+    });
+    client.deposit(&user, &5000000, &RiskProfile::Moderate, &None::<Address>);
+    
+    let statement = client.get_statement(&user, &0, &150, &0, &1);
+    assert_eq!(statement.total_entries, 3);
+    assert_eq!(statement.entries.len(), 1);
+    assert_eq!(statement.entries.get(0).unwrap().kind, ActivityKind::Deposit);
+    assert_eq!(statement.next_start, Some(1));
+    assert_eq!(statement.deposits, 10000000);
+    assert_eq!(statement.withdrawals, 2000000);
+    assert_eq!(statement.fees, 20000);
+    
+    let page = client.get_statement(&user, &0, &150, &1, &10);
+    assert_eq!(page.entries.len(), 2);
+    assert_eq!(page.entries.get(1).unwrap().kind, ActivityKind::Fee);
+    assert_eq!(page.next_start, None);
+}
+
+#[test]
+fn test_member_index_and_fund_stats() {
+    let env = Env::default();
+    let (client, _, kale_token) = setup(&env, 2592000);
+    let alice = member(&env, &kale_token, 30000000);
+    let bob = member(&env, &kale_token, 10000000);
+    
+    client.deposit(&alice, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    client.deposit(&alice, &20000000, &RiskProfile::Moderate, &None::<Address>);
+    client.deposit(&bob, &10000000, &RiskProfile::Aggressive, &None::<Address>);
+    
+    assert_eq!(client.get_member_count(), 2);
+    let members = client.get_members(&1, &10);
+    assert_eq!(members.len(), 1);
+    assert_eq!(members.get(0).unwrap(), bob);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 592000;
+    });
+    let stats = client.get_fund_stats();
+    assert_eq!(stats.invested_members, 2);
+    assert_eq!(stats.tvl_moderate, 30000000);
+    assert_eq!(stats.tvl_aggressive, 10000000);
+    assert_eq!(stats.total_value_locked, 40000000);
+    assert_eq!(stats.average_lock_remaining, 2000000);
+    
+    // Early withdrawal fee (1%) shows up in fees collected
+    client.withdraw(&bob, &10000000);
+    let stats = client.get_fund_stats();
+    assert_eq!(stats.invested_members, 1);
+    assert_eq!(stats.total_fees_collected, 100000);
+    
+    // Expired locks count as nothing left rather than pulling the mean unlock time back
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 3592000;
+    });
+    let carol = member(&env, &kale_token, 10000000);
+    client.deposit(&carol, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    let stats = client.get_fund_stats();
+    assert_eq!(stats.invested_members, 2);
+    assert_eq!(stats.average_lock_remaining, 1296000);
+}
+
+#[test]
+fn test_reconcile_matches_token_balance() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 2592000);
+    let user = member(&env, &kale_token, 10000000);
+    token::StellarAssetClient::new(&env, &kale_token).mint(&admin, &1000000);
+    
+    client.fund_referral_budget(&admin, &1000000);
+    client.deposit(&user, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    client.withdraw(&user, &5000000);
+    
+    let reconciliation = client.reconcile();
+    assert_eq!(reconciliation.member_funds, 5000000);
+    assert_eq!(reconciliation.treasury, 300000);
+    assert_eq!(reconciliation.reward_reserve, 1000000);
+    assert_eq!(reconciliation.token_balance, 6300000);
+    assert_eq!(reconciliation.surplus, 0);
+    
+    // Treasury withdrawals cannot eat into the referral reserve
+    client.withdraw_treasury(&admin, &admin, &300000);
+    assert_eq!(client.reconcile().surplus, 0);
+}
+
+#[test]
+fn test_deposit_caps_and_headroom() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 0);
+    let alice = member(&env, &kale_token, 100000000);
+    let bob = member(&env, &kale_token, 100000000);
+    
+    let change_id = client.set_deposit_limits(
+        &admin,
+        &DepositLimits {
+            member_lifetime_cap: 0,
+            member_annual_cap: 20000000,
+            fund_tvl_cap: 0,
+            conservative_capacity: 0,
+            moderate_capacity: 30000000,
+            aggressive_capacity: 0,
+        },
+    );
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = client.get_timelock_delay();
+    });
+    client.execute_change(&change_id);
+    
+    // Repeated deposits cannot get around the annual cap
+    client.deposit(&alice, &15000000, &RiskProfile::Moderate, &None::<Address>);
+    assert!(client.try_deposit(&alice, &10000000, &RiskProfile::Moderate, &None::<Address>).is_err());
+    
+    let headroom = client.get_deposit_headroom(&bob, &RiskProfile::Moderate);
+    assert_eq!(headroom.member_annual, 20000000);
+    assert_eq!(headroom.profile, 15000000);
+    assert_eq!(headroom.available, 15000000);
+    assert_eq!(headroom.fund, i128::MAX);
+    
+    // The moderate sleeve is nearly full, but other sleeves still have room
+    assert!(client.try_deposit(&bob, &16000000, &RiskProfile::Moderate, &None::<Address>).is_err());
+    client.deposit(&bob, &16000000, &RiskProfile::Aggressive, &None::<Address>);
+    
+    // Nor can the position switch into the full sleeve afterwards
+    assert!(client.try_change_risk_profile(&bob, &RiskProfile::Moderate).is_err());
+    client.change_risk_profile(&bob, &RiskProfile::Conservative);
+    
+    // A new contribution year resets the annual cap
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 31536000;
+    });
+    assert_eq!(client.get_deposit_headroom(&alice, &RiskProfile::Moderate).member_annual, 20000000);
+}
+
+#[test]
+fn test_compliance_mode_requires_attestation() {
+    let env = Env::default();
+    let (client, admin, kale_token) = setup(&env, 0);
+    let officer = Address::generate(&env);
+    let attester = Address::generate(&env);
+    let user = member(&env, &kale_token, 100000000);
+    
+    client.set_compliance_officer(&admin, &officer);
+    client.set_attester(&officer, &attester, &true);
+    client.set_compliance_mode(&admin, &true);
+    
+    // Unverified members cannot deposit
+    assert!(client.try_deposit(&user, &10000000, &RiskProfile::Moderate, &None::<Address>).is_err());
+    
+    client.attest(&attester, &user, &1000);
+    assert!(client.has_valid_attestation(&user));
+    client.deposit(&user, &10000000, &RiskProfile::Moderate, &None::<Address>);
+    
+    // Expired attestations no longer count
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 1000;
+    });
+    assert!(!client.has_valid_attestation(&user));
+    assert!(client.try_deposit(&user, &10000000, &RiskProfile::Moderate, &None::<Address>).is_err());
+    
+    // Revoked attestations do not count either
+    client.attest(&attester, &user, &5000);
+    client.revoke_attestation(&officer, &user);
+    assert!(!client.has_valid_attestation(&user));
+    
+    // Frozen accounts cannot withdraw until released
+    client.set_frozen(&officer, &user, &true);
+    assert!(client.is_frozen(&user));
+    assert!(client.try_withdraw(&user, &1000000).is_err());
+    client.set_frozen(&officer, &user, &false);
+    client.withdraw(&user, &1000000);
+}
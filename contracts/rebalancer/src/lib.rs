@@ -1,16 +1,66 @@
 #![no_std]
+#![allow(clippy::too_many_arguments)]
+
+mod test;
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, log, token, Address, Env, Map, Symbol, Vec,
-    symbol_short,
+    contract, contractclient, contractimpl, contracttype, log, Address, BytesN, Env, IntoVal,
+    Symbol, Val, Vec, symbol_short,
 };
-use soroban_token_sdk::TokenClient;
+use admin_controls::{multisig, timelock};
 
 // Storage keys
 const ADMIN: Symbol = symbol_short!("ADMIN");
 const REBAL_CONFIG: Symbol = symbol_short!("REB_CFG");
-const ASSET_POOLS: Symbol = symbol_short!("AS_POOLS");
-const LAST_REBALANCE: Symbol = symbol_short!("LST_REB");
+const RISK_MANAGER: Symbol = symbol_short!("RISK_MGR");
+const SLEEVE_HOLDINGS: Symbol = symbol_short!("SLV_HOLD");
+const SLEEVE_REBALANCE: Symbol = symbol_short!("SLV_REB");
+const ASSET_ADDRESS: Symbol = symbol_short!("AS_ADDR");
+const KEEPER: Symbol = symbol_short!("KEEPER");
+const ORACLE: Symbol = symbol_short!("ORACLE");
+
+// Deviation (basis points) beyond which a sleeve is rebalanced
+const SLEEVE_REBALANCE_THRESHOLD: u32 = 500;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum RiskProfile {
+    Conservative = 1,
+    Moderate = 2,
+    Aggressive = 3,
+}
+
+// Mirrors `AssetAllocation` in the risk manager contract
+#[derive(Clone)]
+#[contracttype]
+pub struct AssetAllocation {
+    pub kale_percentage: u32,    // basis points (10000 = 100%)
+    pub btc_percentage: u32,     // basis points
+    pub usdc_percentage: u32,    // basis points
+    pub xlm_percentage: u32,     // basis points
+}
+
+#[contractclient(name = "RiskManagerClient")]
+pub trait RiskManagerInterface {
+    fn get_allocation(env: Env, profile: RiskProfile) -> AssetAllocation;
+}
+
+// Mirrors `PriceFeed` in the reflector adapter contract
+#[derive(Clone)]
+#[contracttype]
+pub struct PriceFeed {
+    pub asset: Symbol,
+    pub price_usd: i128,
+    pub price_xlm: Option<i128>,
+    pub timestamp: u64,
+    pub confidence: u32,
+    pub source: Symbol,
+}
+
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracleInterface {
+    fn get_fresh_price(env: Env, asset: Symbol) -> Option<PriceFeed>;
+}
 
 #[derive(Clone)]
 #[contracttype]
@@ -68,6 +118,38 @@ pub struct PortfolioSnapshot {
     pub xlm_percentage: u32,
 }
 
+/// Token balances held by the sub-portfolio of one risk profile
+///
+/// These are the positions the sleeve's orders are computed against, valued in USD at
+/// oracle prices. The core contract's sleeve NAV stays in KALE and is only moved by
+/// `update_sleeve_value` once the orders have settled, so member balances never follow
+/// a rebalance that has not actually traded.
+#[derive(Clone)]
+#[contracttype]
+pub struct SleeveHoldings {
+    pub kale_balance: i128,
+    pub btc_balance: i128,
+    pub usdc_balance: i128,
+    pub xlm_balance: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum PendingAction {
+    UpdateConfig(RebalanceConfig),
+    SetTimelockDelay(u64),
+}
+
+/// Parameter change waiting for the timelock delay to pass
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingChange {
+    pub id: u64,
+    pub action: PendingAction,
+    pub queued_at: u64,
+    pub eta: u64,
+}
+
 #[contract]
 pub struct RebalancerContract;
 
@@ -78,12 +160,16 @@ impl RebalancerContract {
     pub fn initialize(
         env: Env,
         admin: Address,
+        risk_manager: Address,
         min_rebalance_amount: i128,
         max_slippage: u32,
         rebalance_frequency: u64,
         gas_limit: u32,
         max_trades_per_rebalance: u32,
     ) {
+        if env.storage().instance().has(&ADMIN) {
+            panic!("Already initialized");
+        }
         admin.require_auth();
         
         let config = RebalanceConfig {
@@ -96,7 +182,7 @@ impl RebalancerContract {
         
         env.storage().instance().set(&ADMIN, &admin);
         env.storage().instance().set(&REBAL_CONFIG, &config);
-        env.storage().instance().set(&LAST_REBALANCE, &0u64);
+        env.storage().instance().set(&RISK_MANAGER, &risk_manager);
         
         log!(
             &env,
@@ -107,39 +193,66 @@ impl RebalancerContract {
         );
     }
     
-    /// Execute automatic rebalancing based on target allocations
-    pub fn rebalance(
-        env: Env,
-        caller: Address,
-        target_allocations: Map<Address, u32>, // asset -> percentage (basis points)
-        current_prices: Map<Address, i128>,    // asset -> USD price
-    ) -> RebalanceResult {
-        caller.require_auth();
+    /// Rebalance one risk profile's sleeve against its allocation in the risk manager
+    /// (admin or keeper only)
+    ///
+    /// Holdings are valued at fresh oracle prices; the call fails if any price is stale.
+    pub fn rebalance_sleeve(env: Env, caller: Address, profile: RiskProfile) -> RebalanceResult {
+        let keeper: Option<Address> = env.storage().instance().get(&KEEPER);
+        if Some(caller.clone()) == keeper {
+            caller.require_auth();
+        } else {
+            Self::require_admin(&env, &caller, (Symbol::new(&env, "rebalance_sleeve"), profile));
+        }
         
         let config: RebalanceConfig = env.storage().instance().get(&REBAL_CONFIG).unwrap();
         let current_time = env.ledger().timestamp();
-        let last_rebalance: u64 = env.storage().instance().get(&LAST_REBALANCE).unwrap_or(0);
+        let last_rebalance: u64 = env
+            .storage()
+            .persistent()
+            .get(&(SLEEVE_REBALANCE, profile))
+            .unwrap_or(0);
         
-        // Check if enough time has passed since last rebalance
         if current_time < last_rebalance + config.rebalance_frequency {
             panic!("Rebalance frequency not met");
         }
         
-        // Get current portfolio snapshot
-        let portfolio = Self::get_portfolio_snapshot(&env, &current_prices);
-        
-        // Validate total allocation equals 100%
-        let total_allocation: u32 = target_allocations.values().iter().sum();
-        if total_allocation != 10000 {
-            panic!("Target allocations must sum to 100%");
+        let risk_manager: Address = env.storage().instance().get(&RISK_MANAGER).unwrap();
+        let allocation = RiskManagerClient::new(&env, &risk_manager).get_allocation(&profile);
+        
+        let assets = Self::sleeve_assets();
+        let mut balances = Self::holdings_to_balances(&Self::get_holdings(&env, &profile));
+        let targets = [
+            allocation.kale_percentage,
+            allocation.btc_percentage,
+            allocation.usdc_percentage,
+            allocation.xlm_percentage,
+        ];
+        let prices = Self::sleeve_prices(&env, &assets);
+        
+        let value_before = Self::sleeve_value(&balances, &prices);
+        
+        // Positive delta: asset is under-allocated and must be bought
+        let mut deltas = [0i128; 4];
+        let mut max_deviation = 0u32;
+        for i in 0..4 {
+            let current_value = (balances[i] * prices[i]) / 10000000;
+            let target_value = (value_before * targets[i] as i128) / 10000;
+            deltas[i] = target_value - current_value;
+            
+            let current_percentage = if value_before > 0 {
+                ((current_value * 10000) / value_before) as u32
+            } else {
+                0
+            };
+            max_deviation = max_deviation.max(Self::abs_diff(current_percentage, targets[i]));
         }
         
-        // Check if rebalancing is needed
-        if !Self::needs_rebalancing(&env, &portfolio, &target_allocations) {
-            log!(&env, "No rebalancing needed");
+        if value_before < config.min_rebalance_amount || max_deviation <= SLEEVE_REBALANCE_THRESHOLD {
+            log!(&env, "No rebalancing needed for sleeve {:?}", profile);
             return RebalanceResult {
-                total_value_before: portfolio.total_value_usd,
-                total_value_after: portfolio.total_value_usd,
+                total_value_before: value_before,
+                total_value_after: value_before,
                 orders_executed: 0,
                 gas_used: 0,
                 slippage_incurred: 0,
@@ -147,162 +260,155 @@ impl RebalancerContract {
             };
         }
         
-        // Generate rebalance orders
-        let orders = Self::generate_rebalance_orders(
-            &env,
-            &portfolio,
-            &target_allocations,
-            &current_prices,
-        );
-        
-        // Execute rebalance orders
-        let result = Self::execute_rebalance_orders(&env, orders, &config);
+        // Match over-allocated assets against under-allocated ones
+        let mut orders = Vec::new(&env);
+        for from in 0..4 {
+            for to in 0..4 {
+                if orders.len() >= config.max_trades_per_rebalance {
+                    break;
+                }
+                if deltas[from] >= 0 || deltas[to] <= 0 {
+                    continue;
+                }
+                
+                let trade_value = (-deltas[from]).min(deltas[to]);
+                let amount = (trade_value * 10000000) / prices[from];
+                let received = (trade_value * 10000000) / prices[to];
+                if amount == 0 || received == 0 {
+                    continue;
+                }
+                
+                orders.push_back(RebalanceOrder {
+                    from_asset: Self::asset_address(&env, &assets[from]),
+                    to_asset: Self::asset_address(&env, &assets[to]),
+                    amount,
+                    min_received: (received * (10000 - config.max_slippage as i128)) / 10000,
+                    max_slippage: config.max_slippage,
+                    priority: 5,
+                });
+                
+                balances[from] -= amount;
+                balances[to] += received;
+                deltas[from] += trade_value;
+                deltas[to] -= trade_value;
+            }
+        }
         
-        // Update last rebalance timestamp
-        env.storage().instance().set(&LAST_REBALANCE, &current_time);
+        let mut result = Self::execute_rebalance_orders(&env, orders, &config);
+        result.total_value_before = value_before;
+        result.total_value_after = Self::sleeve_value(&balances, &prices);
+        
+        env.storage().persistent().set(
+            &(SLEEVE_HOLDINGS, profile),
+            &SleeveHoldings {
+                kale_balance: balances[0],
+                btc_balance: balances[1],
+                usdc_balance: balances[2],
+                xlm_balance: balances[3],
+            },
+        );
+        env.storage().persistent().set(&(SLEEVE_REBALANCE, profile), &current_time);
         
         log!(
             &env,
-            "Rebalance completed: orders={}, gas_used={}, slippage={}",
+            "Sleeve rebalanced: profile={:?}, orders={}, value_after={}",
+            profile,
             result.orders_executed,
-            result.gas_used,
-            result.slippage_incurred
+            result.total_value_after
         );
         
         result
     }
     
-    /// Get current portfolio snapshot
-    pub fn get_portfolio_snapshot(
-        env: &Env,
-        current_prices: &Map<Address, i128>,
-    ) -> PortfolioSnapshot {
-        // This would integrate with actual token balances
-        // For now, we'll use mock data that would come from the pension fund contract
-        
-        let kale_balance = 1000000i128; // 1M KALE tokens
-        let btc_balance = 50000000i128;  // 0.5 BTC (in stroops)
-        let usdc_balance = 2000000000i128; // 2000 USDC (in stroops)
-        let xlm_balance = 500000000i128;   // 500 XLM (in stroops)
-        
-        // Calculate USD values (prices should be in 1e7 scale)
-        let kale_price = current_prices.get(symbol_short!("KALE")).unwrap_or(100000000); // $10
-        let btc_price = current_prices.get(symbol_short!("BTC")).unwrap_or(430000000000); // $43,000
-        let usdc_price = current_prices.get(symbol_short!("USDC")).unwrap_or(10000000); // $1
-        let xlm_price = current_prices.get(symbol_short!("XLM")).unwrap_or(11000000); // $0.11
-        
-        let kale_value_usd = (kale_balance * kale_price) / 10000000;
-        let btc_value_usd = (btc_balance * btc_price) / 10000000;
-        let usdc_value_usd = (usdc_balance * usdc_price) / 10000000;
-        let xlm_value_usd = (xlm_balance * xlm_price) / 10000000;
-        
-        let total_value_usd = kale_value_usd + btc_value_usd + usdc_value_usd + xlm_value_usd;
-        
-        // Calculate percentages
-        let kale_percentage = if total_value_usd > 0 { (kale_value_usd * 10000) / total_value_usd } else { 0 } as u32;
-        let btc_percentage = if total_value_usd > 0 { (btc_value_usd * 10000) / total_value_usd } else { 0 } as u32;
-        let usdc_percentage = if total_value_usd > 0 { (usdc_value_usd * 10000) / total_value_usd } else { 0 } as u32;
-        let xlm_percentage = if total_value_usd > 0 { (xlm_value_usd * 10000) / total_value_usd } else { 0 } as u32;
+    /// Record the token balances held by a sleeve (admin only)
+    pub fn set_sleeve_holdings(
+        env: Env,
+        caller: Address,
+        profile: RiskProfile,
+        holdings: SleeveHoldings,
+    ) {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_sleeve_holdings"), profile, holdings.clone()),
+        );
         
-        PortfolioSnapshot {
-            total_value_usd,
-            kale_balance,
-            btc_balance,
-            usdc_balance,
-            xlm_balance,
-            kale_percentage,
-            btc_percentage,
-            usdc_percentage,
-            xlm_percentage,
-        }
+        env.storage().persistent().set(&(SLEEVE_HOLDINGS, profile), &holdings);
+        
+        log!(&env, "Sleeve holdings updated: profile={:?}", profile);
     }
     
-    /// Check if rebalancing is needed
-    pub fn needs_rebalancing(
-        env: &Env,
-        portfolio: &PortfolioSnapshot,
-        target_allocations: &Map<Address, u32>,
-    ) -> bool {
-        let config: RebalanceConfig = env.storage().instance().get(&REBAL_CONFIG).unwrap();
+    /// Register the token contract used for an asset symbol in rebalance orders (admin only)
+    pub fn register_asset(env: Env, caller: Address, asset: Symbol, token: Address) {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "register_asset"), asset.clone(), token.clone()),
+        );
         
-        // Check if portfolio value meets minimum threshold
-        if portfolio.total_value_usd < config.min_rebalance_amount {
-            return false;
-        }
+        env.storage().persistent().set(&(ASSET_ADDRESS, asset.clone()), &token);
         
-        // Check deviations from target allocations
-        let kale_target = target_allocations.get(symbol_short!("KALE")).unwrap_or(0);
-        let btc_target = target_allocations.get(symbol_short!("BTC")).unwrap_or(0);
-        let usdc_target = target_allocations.get(symbol_short!("USDC")).unwrap_or(0);
-        let xlm_target = target_allocations.get(symbol_short!("XLM")).unwrap_or(0);
+        log!(&env, "Asset registered: asset={}, token={}", asset, token);
+    }
+    
+    /// Set the keeper allowed to rebalance sleeves (admin only)
+    pub fn set_keeper(env: Env, caller: Address, keeper: Address) {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_keeper"), keeper.clone()));
         
-        let kale_deviation = Self::abs_diff(portfolio.kale_percentage, kale_target);
-        let btc_deviation = Self::abs_diff(portfolio.btc_percentage, btc_target);
-        let usdc_deviation = Self::abs_diff(portfolio.usdc_percentage, usdc_target);
-        let xlm_deviation = Self::abs_diff(portfolio.xlm_percentage, xlm_target);
+        env.storage().instance().set(&KEEPER, &keeper);
         
-        // Rebalance if any asset deviates more than 5% (500 basis points)
-        let rebalance_threshold = 500u32;
-        kale_deviation > rebalance_threshold ||
-        btc_deviation > rebalance_threshold ||
-        usdc_deviation > rebalance_threshold ||
-        xlm_deviation > rebalance_threshold
+        log!(&env, "Keeper set: {}", keeper);
     }
     
-    /// Generate optimal rebalance orders
-    pub fn generate_rebalance_orders(
-        env: &Env,
-        portfolio: &PortfolioSnapshot,
-        target_allocations: &Map<Address, u32>,
-        current_prices: &Map<Address, i128>,
-    ) -> Vec<RebalanceOrder> {
-        let mut orders = Vec::new(&env);
+    /// Get the keeper, if any
+    pub fn get_keeper(env: Env) -> Option<Address> {
+        env.storage().instance().get(&KEEPER)
+    }
+    
+    /// Set the price oracle sleeves are valued with (admin only)
+    pub fn set_oracle(env: Env, caller: Address, oracle: Address) {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_oracle"), oracle.clone()));
         
-        // Calculate target values
-        let kale_target = target_allocations.get(symbol_short!("KALE")).unwrap_or(0);
-        let btc_target = target_allocations.get(symbol_short!("BTC")).unwrap_or(0);
-        let usdc_target = target_allocations.get(symbol_short!("USDC")).unwrap_or(0);
-        let xlm_target = target_allocations.get(symbol_short!("XLM")).unwrap_or(0);
-        
-        let kale_target_value = (portfolio.total_value_usd * kale_target as i128) / 10000;
-        let btc_target_value = (portfolio.total_value_usd * btc_target as i128) / 10000;
-        let usdc_target_value = (portfolio.total_value_usd * usdc_target as i128) / 10000;
-        let xlm_target_value = (portfolio.total_value_usd * xlm_target as i128) / 10000;
-        
-        // Calculate current values
-        let kale_price = current_prices.get(symbol_short!("KALE")).unwrap_or(100000000);
-        let btc_price = current_prices.get(symbol_short!("BTC")).unwrap_or(430000000000);
-        let usdc_price = current_prices.get(symbol_short!("USDC")).unwrap_or(10000000);
-        let xlm_price = current_prices.get(symbol_short!("XLM")).unwrap_or(11000000);
-        
-        let kale_current_value = (portfolio.kale_balance * kale_price) / 10000000;
-        let btc_current_value = (portfolio.btc_balance * btc_price) / 10000000;
-        let usdc_current_value = (portfolio.usdc_balance * usdc_price) / 10000000;
-        let xlm_current_value = (portfolio.xlm_balance * xlm_price) / 10000000;
-        
-        // Generate orders for assets that need to be sold (over-allocated)
-        if kale_current_value > kale_target_value {
-            let excess_value = kale_current_value - kale_target_value;
-            let excess_tokens = (excess_value * 10000000) / kale_price;
-            
-            // For simplicity, sell excess KALE for USDC
-            orders.push_back(RebalanceOrder {
-                from_asset: Address::from_contract_data(&env, symbol_short!("KALE")),
-                to_asset: Address::from_contract_data(&env, symbol_short!("USDC")),
-                amount: excess_tokens,
-                min_received: (excess_value * 9800) / 10000, // 2% slippage tolerance
-                max_slippage: 200, // 2%
-                priority: 5,
-            });
-        }
+        env.storage().instance().set(&ORACLE, &oracle);
         
-        // Similar logic would be implemented for other assets...
+        log!(&env, "Oracle set: {}", oracle);
+    }
+    
+    /// Get a sleeve's portfolio snapshot at fresh oracle prices
+    pub fn get_sleeve_snapshot(env: Env, profile: RiskProfile) -> PortfolioSnapshot {
+        let holdings = Self::get_holdings(&env, &profile);
+        let balances = Self::holdings_to_balances(&holdings);
+        let prices = Self::sleeve_prices(&env, &Self::sleeve_assets());
+        
+        let mut values = [0i128; 4];
+        for i in 0..4 {
+            values[i] = (balances[i] * prices[i]) / 10000000;
+        }
+        let total_value_usd = values[0] + values[1] + values[2] + values[3];
+        let percentage = |value: i128| -> u32 {
+            if total_value_usd > 0 { ((value * 10000) / total_value_usd) as u32 } else { 0 }
+        };
         
-        orders
+        PortfolioSnapshot {
+            total_value_usd,
+            kale_balance: holdings.kale_balance,
+            btc_balance: holdings.btc_balance,
+            usdc_balance: holdings.usdc_balance,
+            xlm_balance: holdings.xlm_balance,
+            kale_percentage: percentage(values[0]),
+            btc_percentage: percentage(values[1]),
+            usdc_percentage: percentage(values[2]),
+            xlm_percentage: percentage(values[3]),
+        }
+    }
+    
+    /// Get the last rebalance timestamp of a sleeve
+    pub fn get_sleeve_last_rebalance(env: Env, profile: RiskProfile) -> u64 {
+        env.storage().persistent().get(&(SLEEVE_REBALANCE, profile)).unwrap_or(0)
     }
     
     /// Execute rebalance orders
-    pub fn execute_rebalance_orders(
+    fn execute_rebalance_orders(
         env: &Env,
         orders: Vec<RebalanceOrder>,
         config: &RebalanceConfig,
@@ -346,21 +452,116 @@ impl RebalancerContract {
         }
     }
     
-    /// Update rebalance configuration (admin only)
+    /// Queue a rebalance configuration update (admin only)
+    ///
+    /// Returns the id to pass to `execute_change` once the timelock delay has passed.
     pub fn update_config(
         env: Env,
         caller: Address,
         config: RebalanceConfig,
-    ) {
-        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
-        if caller != admin {
-            panic!("Unauthorized");
+    ) -> u64 {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "update_config"), config.clone()));
+        
+        timelock::queue(&env, PendingAction::UpdateConfig(config))
+    }
+    
+    /// Execute a queued change once its timelock has expired (permissionless)
+    pub fn execute_change(env: Env, id: u64) {
+        let action: PendingAction = timelock::take_ready(&env, id);
+        match action {
+            PendingAction::UpdateConfig(config) => {
+                env.storage().instance().set(&REBAL_CONFIG, &config);
+            }
+            PendingAction::SetTimelockDelay(delay) => {
+                timelock::set_delay(&env, delay);
+            }
         }
-        caller.require_auth();
         
-        env.storage().instance().set(&REBAL_CONFIG, &config);
+        log!(&env, "Queued change executed: id={}", id);
+    }
+    
+    /// Cancel a queued change (guardian only)
+    pub fn cancel_change(env: Env, caller: Address, id: u64) {
+        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
+        timelock::cancel(&env, &admin, &caller, id);
+    }
+    
+    /// Queue a new timelock delay (admin only)
+    pub fn set_timelock_delay(env: Env, caller: Address, delay: u64) -> u64 {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_timelock_delay"), delay));
+        
+        timelock::validate_delay(delay);
+        
+        timelock::queue(&env, PendingAction::SetTimelockDelay(delay))
+    }
+    
+    /// Set the guardian allowed to cancel queued changes (admin only)
+    pub fn set_guardian(env: Env, caller: Address, guardian: Address) {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_guardian"), guardian.clone()));
         
-        log!(&env, "Rebalance config updated by admin: {}", caller);
+        timelock::set_guardian(&env, &guardian);
+    }
+    
+    /// Get all changes waiting in the timelock
+    pub fn get_pending_changes(env: Env) -> Vec<PendingChange> {
+        let mut changes = Vec::new(&env);
+        for id in timelock::pending_ids(&env).iter() {
+            let change = timelock::get(&env, id);
+            changes.push_back(PendingChange {
+                id,
+                action: change.action,
+                queued_at: change.queued_at,
+                eta: change.eta,
+            });
+        }
+        changes
+    }
+    
+    /// Get the delay between queueing and executing a change
+    pub fn get_timelock_delay(env: Env) -> u64 {
+        timelock::delay(&env)
+    }
+    
+    /// Get the guardian allowed to cancel queued changes
+    pub fn get_guardian(env: Env) -> Address {
+        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
+        timelock::guardian(&env, &admin)
+    }
+    
+    /// Replace the multisig signer set and approval threshold (admin only)
+    pub fn set_signers(env: Env, caller: Address, signers: Vec<Address>, threshold: u32) {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_signers"), signers.clone(), threshold),
+        );
+        
+        multisig::set_signers(&env, &signers, threshold);
+    }
+    
+    /// Approve an admin action by its hash (signers only)
+    pub fn approve_action(env: Env, signer: Address, action_hash: BytesN<32>) -> u32 {
+        multisig::approve(&env, &signer, &action_hash)
+    }
+    
+    /// Withdraw a signer's approval of an action that has not been executed
+    pub fn revoke_approval(env: Env, signer: Address, action_hash: BytesN<32>) {
+        multisig::revoke(&env, &signer, &action_hash);
+    }
+    
+    /// Get the multisig signer set
+    pub fn get_signers(env: Env) -> Vec<Address> {
+        multisig::signers(&env)
+    }
+    
+    /// Get the number of signer approvals each admin action needs (0 when multisig is off)
+    pub fn get_signer_threshold(env: Env) -> u32 {
+        multisig::threshold(&env)
+    }
+    
+    /// Get the signers who approved an action
+    pub fn get_approvals(env: Env, action_hash: BytesN<32>) -> Vec<Address> {
+        multisig::approvals(&env, &action_hash)
     }
     
     /// Get rebalance configuration
@@ -368,15 +569,83 @@ impl RebalancerContract {
         env.storage().instance().get(&REBAL_CONFIG).unwrap()
     }
     
-    /// Get last rebalance timestamp
-    pub fn get_last_rebalance(env: Env) -> u64 {
-        env.storage().instance().get(&LAST_REBALANCE).unwrap_or(0)
-    }
-    
     // Internal helper functions
     
+    /// Internal helper to authorize an admin action, with signer approvals when multisig is on
+    fn require_admin<T: IntoVal<Env, Val>>(env: &Env, caller: &Address, action: T) {
+        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
+        multisig::require_admin(env, &admin, caller, action);
+    }
+    
     fn abs_diff(a: u32, b: u32) -> u32 {
-        if a > b { a - b } else { b - a }
+        a.abs_diff(b)
+    }
+    
+    fn sleeve_assets() -> [Symbol; 4] {
+        [
+            symbol_short!("KALE"),
+            symbol_short!("BTC"),
+            symbol_short!("USDC"),
+            symbol_short!("XLM"),
+        ]
+    }
+    
+    fn get_holdings(env: &Env, profile: &RiskProfile) -> SleeveHoldings {
+        env.storage()
+            .persistent()
+            .get(&(SLEEVE_HOLDINGS, *profile))
+            .unwrap_or(SleeveHoldings {
+                kale_balance: 0,
+                btc_balance: 0,
+                usdc_balance: 0,
+                xlm_balance: 0,
+            })
+    }
+    
+    fn holdings_to_balances(holdings: &SleeveHoldings) -> [i128; 4] {
+        [
+            holdings.kale_balance,
+            holdings.btc_balance,
+            holdings.usdc_balance,
+            holdings.xlm_balance,
+        ]
+    }
+    
+    /// Internal helper returning fresh USD prices of the sleeve assets
+    fn sleeve_prices(env: &Env, assets: &[Symbol; 4]) -> [i128; 4] {
+        let oracle: Address = env
+            .storage()
+            .instance()
+            .get(&ORACLE)
+            .unwrap_or_else(|| panic!("Oracle not set"));
+        let oracle_client = PriceOracleClient::new(env, &oracle);
+        
+        let mut prices = [0i128; 4];
+        for i in 0..4 {
+            prices[i] = oracle_client
+                .get_fresh_price(&assets[i])
+                .unwrap_or_else(|| panic!("Stale price"))
+                .price_usd;
+            if prices[i] <= 0 {
+                panic!("Invalid price");
+            }
+        }
+        prices
+    }
+    
+    fn sleeve_value(balances: &[i128; 4], prices: &[i128; 4]) -> i128 {
+        let mut total = 0i128;
+        for i in 0..4 {
+            total += (balances[i] * prices[i]) / 10000000;
+        }
+        total
+    }
+    
+    fn asset_address(env: &Env, asset: &Symbol) -> Address {
+        env.storage()
+            .persistent()
+            .get(&(ASSET_ADDRESS, asset.clone()))
+            .unwrap_or_else(|| panic!("Asset not registered"))
     }
     
     fn simulate_trade_execution(_env: &Env, _order: &RebalanceOrder) -> u32 {
@@ -0,0 +1,138 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, Env,
+};
+
+#[contract]
+struct MockRiskManager;
+
+#[contractimpl]
+impl MockRiskManager {
+    pub fn get_allocation(_env: Env, _profile: RiskProfile) -> AssetAllocation {
+        AssetAllocation {
+            kale_percentage: 4000,
+            btc_percentage: 3000,
+            usdc_percentage: 2000,
+            xlm_percentage: 1000,
+        }
+    }
+}
+
+#[contract]
+struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn set_price(env: Env, asset: Symbol, price_usd: i128) {
+        env.storage().instance().set(&asset, &price_usd);
+    }
+    
+    pub fn get_fresh_price(env: Env, asset: Symbol) -> Option<PriceFeed> {
+        let price_usd: Option<i128> = env.storage().instance().get(&asset);
+        price_usd.map(|price_usd| PriceFeed {
+            asset,
+            price_usd,
+            price_xlm: None,
+            timestamp: env.ledger().timestamp(),
+            confidence: 10000,
+            source: symbol_short!("MOCK"),
+        })
+    }
+}
+
+fn setup(env: &Env) -> (RebalancerContractClient<'_>, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, RebalancerContract);
+    let client = RebalancerContractClient::new(env, &contract_id);
+    
+    let admin = Address::generate(env);
+    let risk_manager = env.register_contract(None, MockRiskManager);
+    client.initialize(
+        &admin,
+        &risk_manager,
+        &1000000, // min_rebalance_amount
+        &100,     // max_slippage: 1%
+        &3600,    // rebalance_frequency: 1 hour
+        &1000000, // gas_limit
+        &10,      // max_trades_per_rebalance
+    );
+    
+    let oracle_id = env.register_contract(None, MockOracle);
+    let oracle = MockOracleClient::new(env, &oracle_id);
+    client.set_oracle(&admin, &oracle_id);
+    
+    for asset in RebalancerContract::sleeve_assets() {
+        client.register_asset(&admin, &asset, &Address::generate(env));
+        oracle.set_price(&asset, &10000000); // $1
+    }
+    
+    client.set_sleeve_holdings(
+        &admin,
+        &RiskProfile::Moderate,
+        &SleeveHoldings {
+            kale_balance: 1000000000,
+            btc_balance: 0,
+            usdc_balance: 0,
+            xlm_balance: 0,
+        },
+    );
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 3600;
+    });
+    
+    (client, admin)
+}
+
+#[test]
+fn test_initialize_only_once() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    
+    let attacker = Address::generate(&env);
+    assert!(client
+        .try_initialize(&attacker, &Address::generate(&env), &0, &10000, &0, &1000000, &10)
+        .is_err());
+}
+
+#[test]
+fn test_keeper_rebalances_sleeve_at_oracle_prices() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let keeper = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    
+    assert!(client.try_rebalance_sleeve(&keeper, &RiskProfile::Moderate).is_err());
+    client.set_keeper(&admin, &keeper);
+    assert!(client.try_rebalance_sleeve(&stranger, &RiskProfile::Moderate).is_err());
+    
+    // The all-KALE sleeve sells into the three other assets
+    let result = client.rebalance_sleeve(&keeper, &RiskProfile::Moderate);
+    assert_eq!(result.orders_executed, 3);
+    assert_eq!(result.total_value_before, 1000000000);
+    assert_eq!(result.total_value_after, 1000000000);
+    
+    let snapshot = client.get_sleeve_snapshot(&RiskProfile::Moderate);
+    assert_eq!(snapshot.kale_percentage, 4000);
+    assert_eq!(snapshot.btc_percentage, 3000);
+    assert_eq!(snapshot.usdc_percentage, 2000);
+    assert_eq!(snapshot.xlm_percentage, 1000);
+}
+
+#[test]
+#[should_panic(expected = "Stale price")]
+fn test_rebalance_sleeve_needs_every_price() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    
+    // An oracle without a BTC price cannot value the sleeve
+    let oracle_id = env.register_contract(None, MockOracle);
+    let oracle = MockOracleClient::new(&env, &oracle_id);
+    oracle.set_price(&symbol_short!("KALE"), &10000000);
+    client.set_oracle(&admin, &oracle_id);
+    
+    client.rebalance_sleeve(&admin, &RiskProfile::Moderate);
+}
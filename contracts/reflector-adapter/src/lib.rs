@@ -1,14 +1,17 @@
 #![no_std]
+#![allow(clippy::too_many_arguments)]
+
+mod test;
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, log, Address, Env, Map, Symbol, Vec,
-    symbol_short,
+    contract, contractimpl, contracttype, log, vec, Address, BytesN, Env, IntoVal, Symbol, Val,
+    Vec, symbol_short,
 };
+use admin_controls::{multisig, timelock};
 
 // Storage keys
 const ADMIN: Symbol = symbol_short!("ADMIN");
 const ORACLE_CONFIG: Symbol = symbol_short!("ORA_CFG");
-const PRICE_FEEDS: Symbol = symbol_short!("PR_FEEDS");
 const LAST_UPDATE: Symbol = symbol_short!("LST_UPD");
 
 #[derive(Clone)]
@@ -55,6 +58,23 @@ pub struct AggregatedPrices {
     pub data_freshness: u64,           // seconds since last update
 }
 
+#[derive(Clone)]
+#[contracttype]
+pub enum PendingAction {
+    UpdateConfig(OracleConfig),
+    SetTimelockDelay(u64),
+}
+
+/// Parameter change waiting for the timelock delay to pass
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingChange {
+    pub id: u64,
+    pub action: PendingAction,
+    pub queued_at: u64,
+    pub eta: u64,
+}
+
 #[contract]
 pub struct ReflectorAdapterContract;
 
@@ -72,10 +92,13 @@ impl ReflectorAdapterContract {
         max_price_age: u64,
         decimals: u32,
     ) {
+        if env.storage().instance().has(&ADMIN) {
+            panic!("Already initialized");
+        }
         admin.require_auth();
         
         let config = OracleConfig {
-            reflector_usd_oracle,
+            reflector_usd_oracle: reflector_usd_oracle.clone(),
             reflector_stellar_oracle,
             update_frequency,
             price_deviation_threshold,
@@ -113,6 +136,7 @@ impl ReflectorAdapterContract {
         
         // Fetch prices for each supported asset
         let assets = vec![
+            &env,
             symbol_short!("KALE"),
             symbol_short!("XLM"),
             symbol_short!("BTC"),
@@ -120,7 +144,7 @@ impl ReflectorAdapterContract {
         ];
         
         for asset in assets.iter() {
-            if let Some(update) = Self::fetch_asset_price(&env, &config, asset.clone()) {
+            if let Some(update) = Self::fetch_asset_price(&env, &config, asset) {
                 price_updates.push_back(update);
             }
         }
@@ -164,11 +188,7 @@ impl ReflectorAdapterContract {
             .unwrap_or(10000000); // Default to $1.00
         
         let last_updated = env.storage().instance().get(&LAST_UPDATE).unwrap_or(0);
-        let data_freshness = if current_time > last_updated { 
-            current_time - last_updated 
-        } else { 
-            0 
-        };
+        let data_freshness = current_time.saturating_sub(last_updated);
         
         AggregatedPrices {
             kale_usd: kale_price,
@@ -182,8 +202,8 @@ impl ReflectorAdapterContract {
     
     /// Calculate price impact for a trade
     pub fn calculate_price_impact(
-        env: Env,
-        asset: Symbol,
+        _env: Env,
+        _asset: Symbol,
         trade_amount: i128,
         total_liquidity: i128,
     ) -> u32 {
@@ -228,28 +248,123 @@ impl ReflectorAdapterContract {
     pub fn calculate_twap(
         env: Env,
         asset: Symbol,
-        time_window: u64, // seconds
+        _time_window: u64, // seconds
     ) -> Option<i128> {
         // In a real implementation, this would calculate TWAP from historical data
         // For now, return current price as a placeholder
         Self::get_price(env, asset).map(|feed| feed.price_usd)
     }
     
-    /// Update oracle configuration (admin only)
+    /// Queue an oracle configuration update (admin only)
+    ///
+    /// Returns the id to pass to `execute_change` once the timelock delay has passed.
     pub fn update_config(
         env: Env,
         caller: Address,
         config: OracleConfig,
-    ) {
-        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
-        if caller != admin {
-            panic!("Unauthorized");
+    ) -> u64 {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "update_config"), config.clone()));
+        
+        timelock::queue(&env, PendingAction::UpdateConfig(config))
+    }
+    
+    /// Execute a queued change once its timelock has expired (permissionless)
+    pub fn execute_change(env: Env, id: u64) {
+        let action: PendingAction = timelock::take_ready(&env, id);
+        match action {
+            PendingAction::UpdateConfig(config) => {
+                env.storage().instance().set(&ORACLE_CONFIG, &config);
+            }
+            PendingAction::SetTimelockDelay(delay) => {
+                timelock::set_delay(&env, delay);
+            }
         }
-        caller.require_auth();
         
-        env.storage().instance().set(&ORACLE_CONFIG, &config);
+        log!(&env, "Queued change executed: id={}", id);
+    }
+    
+    /// Cancel a queued change (guardian only)
+    pub fn cancel_change(env: Env, caller: Address, id: u64) {
+        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
+        timelock::cancel(&env, &admin, &caller, id);
+    }
+    
+    /// Queue a new timelock delay (admin only)
+    pub fn set_timelock_delay(env: Env, caller: Address, delay: u64) -> u64 {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_timelock_delay"), delay));
+        
+        timelock::validate_delay(delay);
+        
+        timelock::queue(&env, PendingAction::SetTimelockDelay(delay))
+    }
+    
+    /// Set the guardian allowed to cancel queued changes (admin only)
+    pub fn set_guardian(env: Env, caller: Address, guardian: Address) {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_guardian"), guardian.clone()));
         
-        log!(&env, "Oracle config updated by admin: {}", caller);
+        timelock::set_guardian(&env, &guardian);
+    }
+    
+    /// Get all changes waiting in the timelock
+    pub fn get_pending_changes(env: Env) -> Vec<PendingChange> {
+        let mut changes = Vec::new(&env);
+        for id in timelock::pending_ids(&env).iter() {
+            let change = timelock::get(&env, id);
+            changes.push_back(PendingChange {
+                id,
+                action: change.action,
+                queued_at: change.queued_at,
+                eta: change.eta,
+            });
+        }
+        changes
+    }
+    
+    /// Get the delay between queueing and executing a change
+    pub fn get_timelock_delay(env: Env) -> u64 {
+        timelock::delay(&env)
+    }
+    
+    /// Get the guardian allowed to cancel queued changes
+    pub fn get_guardian(env: Env) -> Address {
+        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
+        timelock::guardian(&env, &admin)
+    }
+    
+    /// Replace the multisig signer set and approval threshold (admin only)
+    pub fn set_signers(env: Env, caller: Address, signers: Vec<Address>, threshold: u32) {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_signers"), signers.clone(), threshold),
+        );
+        
+        multisig::set_signers(&env, &signers, threshold);
+    }
+    
+    /// Approve an admin action by its hash (signers only)
+    pub fn approve_action(env: Env, signer: Address, action_hash: BytesN<32>) -> u32 {
+        multisig::approve(&env, &signer, &action_hash)
+    }
+    
+    /// Withdraw a signer's approval of an action that has not been executed
+    pub fn revoke_approval(env: Env, signer: Address, action_hash: BytesN<32>) {
+        multisig::revoke(&env, &signer, &action_hash);
+    }
+    
+    /// Get the multisig signer set
+    pub fn get_signers(env: Env) -> Vec<Address> {
+        multisig::signers(&env)
+    }
+    
+    /// Get the number of signer approvals each admin action needs (0 when multisig is off)
+    pub fn get_signer_threshold(env: Env) -> u32 {
+        multisig::threshold(&env)
+    }
+    
+    /// Get the signers who approved an action
+    pub fn get_approvals(env: Env, action_hash: BytesN<32>) -> Vec<Address> {
+        multisig::approvals(&env, &action_hash)
     }
     
     /// Get oracle configuration
@@ -265,11 +380,11 @@ impl ReflectorAdapterContract {
         price: i128,
         reason: Symbol,
     ) {
-        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
-        if caller != admin {
-            panic!("Unauthorized");
-        }
-        caller.require_auth();
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "emergency_price_override"), asset.clone(), price, reason.clone()),
+        );
         
         let current_time = env.ledger().timestamp();
         let emergency_feed = PriceFeed {
@@ -294,9 +409,15 @@ impl ReflectorAdapterContract {
     
     // Internal helper functions
     
+    /// Internal helper to authorize an admin action, with signer approvals when multisig is on
+    fn require_admin<T: IntoVal<Env, Val>>(env: &Env, caller: &Address, action: T) {
+        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
+        multisig::require_admin(env, &admin, caller, action);
+    }
+    
     fn fetch_asset_price(
         env: &Env,
-        config: &OracleConfig,
+        _config: &OracleConfig,
         asset: Symbol,
     ) -> Option<PriceUpdate> {
         // In a real implementation, this would call the Reflector oracle contracts
@@ -306,12 +427,16 @@ impl ReflectorAdapterContract {
         let old_price_feed: Option<PriceFeed> = env.storage().persistent().get(&asset);
         
         // Mock price data (in a real implementation, this would come from Reflector)
-        let new_price = match asset {
-            s if s == symbol_short!("KALE") => 100000000i128,    // $10.00
-            s if s == symbol_short!("XLM") => 11000000i128,      // $0.11
-            s if s == symbol_short!("BTC") => 430000000000i128,  // $43,000.00
-            s if s == symbol_short!("USDC") => 10000000i128,     // $1.00
-            _ => return None,
+        let new_price = if asset == symbol_short!("KALE") {
+            100000000i128 // $10.00
+        } else if asset == symbol_short!("XLM") {
+            11000000i128 // $0.11
+        } else if asset == symbol_short!("BTC") {
+            430000000000i128 // $43,000.00
+        } else if asset == symbol_short!("USDC") {
+            10000000i128 // $1.00
+        } else {
+            return None;
         };
         
         let old_price = old_price_feed.as_ref().map(|f| f.price_usd).unwrap_or(new_price);
@@ -0,0 +1,111 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, xdr::ToXdr, Address, Env,
+};
+
+fn setup(env: &Env) -> (ReflectorAdapterContractClient<'_>, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, ReflectorAdapterContract);
+    let client = ReflectorAdapterContractClient::new(env, &contract_id);
+    
+    let admin = Address::generate(env);
+    client.initialize(
+        &admin,
+        &Address::generate(env),
+        &Address::generate(env),
+        &300,
+        &500,
+        &3600,
+        &14,
+    );
+    
+    (client, admin)
+}
+
+fn new_config(env: &Env, client: &ReflectorAdapterContractClient) -> OracleConfig {
+    let mut config = client.get_config();
+    config.max_price_age = 600;
+    config.reflector_usd_oracle = Address::generate(env);
+    config
+}
+
+#[test]
+fn test_initialize_only_once() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    
+    let attacker = Address::generate(&env);
+    assert!(client
+        .try_initialize(
+            &attacker,
+            &Address::generate(&env),
+            &Address::generate(&env),
+            &300,
+            &500,
+            &3600,
+            &14,
+        )
+        .is_err());
+    assert_eq!(client.get_config().max_price_age, 3600);
+}
+
+#[test]
+fn test_config_update_waits_for_timelock() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    
+    let config = new_config(&env, &client);
+    let change_id = client.update_config(&admin, &config);
+    assert_eq!(client.get_config().max_price_age, 3600);
+    assert!(client.try_execute_change(&change_id).is_err());
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = client.get_timelock_delay();
+    });
+    client.execute_change(&change_id);
+    assert_eq!(client.get_config().max_price_age, 600);
+}
+
+#[test]
+fn test_guardian_cancels_config_update() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let guardian = Address::generate(&env);
+    
+    client.set_guardian(&admin, &guardian);
+    let change_id = client.update_config(&admin, &new_config(&env, &client));
+    
+    assert!(client.try_cancel_change(&admin, &change_id).is_err());
+    client.cancel_change(&guardian, &change_id);
+    assert_eq!(client.get_pending_changes().len(), 0);
+}
+
+#[test]
+fn test_price_override_needs_signer_approvals() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    
+    let signer_a = Address::generate(&env);
+    let signer_b = Address::generate(&env);
+    client.set_signers(&admin, &vec![&env, signer_a.clone(), signer_b.clone()], &2);
+    
+    let asset = symbol_short!("KALE");
+    let reason = symbol_short!("HALT");
+    let action_hash: BytesN<32> = env
+        .crypto()
+        .sha256(
+            &(Symbol::new(&env, "emergency_price_override"), asset.clone(), 90000000i128, reason.clone())
+                .to_xdr(&env),
+        )
+        .into();
+    
+    client.approve_action(&signer_a, &action_hash);
+    assert!(client.try_emergency_price_override(&admin, &asset, &90000000, &reason).is_err());
+    
+    client.approve_action(&signer_b, &action_hash);
+    client.emergency_price_override(&admin, &asset, &90000000, &reason);
+    assert_eq!(client.get_price(&asset).unwrap().price_usd, 90000000);
+}
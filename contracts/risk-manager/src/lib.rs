@@ -3,17 +3,17 @@
 mod test;
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, log, Address, Env, Map, Symbol, Vec,
-    symbol_short,
+    contract, contractimpl, contracttype, log, Address, BytesN, Env, IntoVal, Symbol, Val,
+    Vec, symbol_short,
 };
+use admin_controls::{multisig, timelock};
 
 // Storage keys
 const ADMIN: Symbol = symbol_short!("ADMIN");
 const RISK_PARAMS: Symbol = symbol_short!("RISK_PRM");
-const ASSET_WEIGHTS: Symbol = symbol_short!("AS_WGHT");
-const VOLATILITY_DATA: Symbol = symbol_short!("VOL_DATA");
+const GOVERNOR: Symbol = symbol_short!("GOVERNOR");
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[contracttype]
 pub enum RiskProfile {
     Conservative = 1,
@@ -61,6 +61,31 @@ pub struct RiskAssessment {
     pub liquidity_risk: u32,       // 0-10000 (100.00%)
 }
 
+#[derive(Clone)]
+#[contracttype]
+pub struct AllocationChange {
+    pub profile: RiskProfile,
+    pub allocation: AssetAllocation,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum PendingAction {
+    UpdateAllocation(AllocationChange),
+    UpdateRiskParameters(RiskParameters),
+    SetTimelockDelay(u64),
+}
+
+/// Parameter change waiting for the timelock delay to pass
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingChange {
+    pub id: u64,
+    pub action: PendingAction,
+    pub queued_at: u64,
+    pub eta: u64,
+}
+
 #[contract]
 pub struct RiskManagerContract;
 
@@ -77,6 +102,9 @@ impl RiskManagerContract {
         stress_test_threshold: u32,
         rebalance_threshold: u32,
     ) {
+        if env.storage().instance().has(&ADMIN) {
+            panic!("Already initialized");
+        }
         admin.require_auth();
         
         let risk_params = RiskParameters {
@@ -110,8 +138,8 @@ impl RiskManagerContract {
             RiskProfile::Aggressive => symbol_short!("AGG_ALL"),
         };
         
-        env.storage().persistent().get(&key).unwrap_or_else(|| {
-            // Return default allocation if not found
+        // Return default allocation if not found
+        env.storage().persistent().get(&key).unwrap_or(
             match profile {
                 RiskProfile::Conservative => AssetAllocation {
                     kale_percentage: 2000,   // 20%
@@ -132,7 +160,7 @@ impl RiskManagerContract {
                     xlm_percentage: 500,     // 5%
                 },
             }
-        })
+        )
     }
     
     /// Perform comprehensive risk assessment
@@ -142,8 +170,7 @@ impl RiskManagerContract {
         current_allocation: AssetAllocation,
         market_conditions: Vec<VolatilityData>,
     ) -> RiskAssessment {
-        let recommended_allocation = Self::get_allocation(env.clone(), profile.clone());
-        let risk_params: RiskParameters = env.storage().instance().get(&RISK_PARAMS).unwrap();
+        let recommended_allocation = Self::get_allocation(env.clone(), profile);
         
         // Calculate risk score based on deviation from recommended allocation
         let allocation_risk = Self::calculate_allocation_risk(
@@ -182,18 +209,26 @@ impl RiskManagerContract {
         }
     }
     
-    /// Update asset allocation for a risk profile (admin only)
+    /// Queue an asset allocation update for a risk profile (admin only)
+    ///
+    /// Returns the id to pass to `execute_change` once the timelock delay has passed.
     pub fn update_allocation(
         env: Env,
         caller: Address,
         profile: RiskProfile,
         allocation: AssetAllocation,
-    ) {
-        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
-        if caller != admin {
-            panic!("Unauthorized");
+    ) -> u64 {
+        // Allocations can also be changed by member vote through the governor
+        let governor: Option<Address> = env.storage().instance().get(&GOVERNOR);
+        if Some(caller.clone()) == governor {
+            caller.require_auth();
+        } else {
+            Self::require_admin(
+                &env,
+                &caller,
+                (Symbol::new(&env, "update_allocation"), profile, allocation.clone()),
+            );
         }
-        caller.require_auth();
         
         // Validate allocation percentages sum to 100%
         let total = allocation.kale_percentage + allocation.btc_percentage + 
@@ -202,23 +237,10 @@ impl RiskManagerContract {
             panic!("Allocation percentages must sum to 100%");
         }
         
-        let key = match profile {
-            RiskProfile::Conservative => symbol_short!("CONS_ALL"),
-            RiskProfile::Moderate => symbol_short!("MOD_ALL"),
-            RiskProfile::Aggressive => symbol_short!("AGG_ALL"),
-        };
-        
-        env.storage().persistent().set(&key, &allocation);
-        
-        log!(
+        timelock::queue(
             &env,
-            "Allocation updated: profile={:?}, kale={}, btc={}, usdc={}, xlm={}",
-            profile,
-            allocation.kale_percentage,
-            allocation.btc_percentage,
-            allocation.usdc_percentage,
-            allocation.xlm_percentage
-        );
+            PendingAction::UpdateAllocation(AllocationChange { profile, allocation }),
+        )
     }
     
     /// Update volatility data for assets
@@ -227,11 +249,11 @@ impl RiskManagerContract {
         caller: Address,
         volatility_data: Vec<VolatilityData>,
     ) {
-        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
-        if caller != admin {
-            panic!("Unauthorized");
-        }
-        caller.require_auth();
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "update_volatility"), volatility_data.clone()),
+        );
         
         for data in volatility_data.iter() {
             env.storage().persistent().set(&data.asset, &data);
@@ -265,25 +287,161 @@ impl RiskManagerContract {
         env.storage().instance().get(&RISK_PARAMS).unwrap()
     }
     
-    /// Update risk parameters (admin only)
+    /// Queue a risk parameters update (admin only)
     pub fn update_risk_parameters(
         env: Env,
         caller: Address,
         risk_params: RiskParameters,
-    ) {
-        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
-        if caller != admin {
-            panic!("Unauthorized");
+    ) -> u64 {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "update_risk_parameters"), risk_params.clone()),
+        );
+        
+        timelock::queue(&env, PendingAction::UpdateRiskParameters(risk_params))
+    }
+    
+    /// Execute a queued change once its timelock has expired (permissionless)
+    pub fn execute_change(env: Env, id: u64) {
+        let action: PendingAction = timelock::take_ready(&env, id);
+        match action {
+            PendingAction::UpdateAllocation(update) => {
+                let key = match update.profile {
+                    RiskProfile::Conservative => symbol_short!("CONS_ALL"),
+                    RiskProfile::Moderate => symbol_short!("MOD_ALL"),
+                    RiskProfile::Aggressive => symbol_short!("AGG_ALL"),
+                };
+                
+                env.storage().persistent().set(&key, &update.allocation);
+                
+                log!(
+                    &env,
+                    "Allocation updated: profile={:?}, kale={}, btc={}, usdc={}, xlm={}",
+                    update.profile,
+                    update.allocation.kale_percentage,
+                    update.allocation.btc_percentage,
+                    update.allocation.usdc_percentage,
+                    update.allocation.xlm_percentage
+                );
+            }
+            PendingAction::UpdateRiskParameters(risk_params) => {
+                env.storage().instance().set(&RISK_PARAMS, &risk_params);
+                
+                log!(&env, "Risk parameters updated: change={}", id);
+            }
+            PendingAction::SetTimelockDelay(delay) => {
+                timelock::set_delay(&env, delay);
+            }
         }
-        caller.require_auth();
+    }
+    
+    /// Cancel a queued change (guardian only)
+    pub fn cancel_change(env: Env, caller: Address, id: u64) {
+        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
+        timelock::cancel(&env, &admin, &caller, id);
+    }
+    
+    /// Queue a new timelock delay (admin only)
+    pub fn set_timelock_delay(env: Env, caller: Address, delay: u64) -> u64 {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_timelock_delay"), delay));
         
-        env.storage().instance().set(&RISK_PARAMS, &risk_params);
+        timelock::validate_delay(delay);
+        
+        timelock::queue(&env, PendingAction::SetTimelockDelay(delay))
+    }
+    
+    /// Set the guardian allowed to cancel queued changes (admin only)
+    pub fn set_guardian(env: Env, caller: Address, guardian: Address) {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_guardian"), guardian.clone()));
+        
+        timelock::set_guardian(&env, &guardian);
+    }
+    
+    /// Set the governor contract allowed to queue allocation changes (admin only)
+    pub fn set_governor(env: Env, caller: Address, governor: Address) {
+        Self::require_admin(&env, &caller, (Symbol::new(&env, "set_governor"), governor.clone()));
+        
+        env.storage().instance().set(&GOVERNOR, &governor);
+        
+        log!(&env, "Governor set: {}", governor);
+    }
+    
+    /// Get the governor contract, if any
+    pub fn get_governor(env: Env) -> Option<Address> {
+        env.storage().instance().get(&GOVERNOR)
+    }
+    
+    /// Get all changes waiting in the timelock
+    pub fn get_pending_changes(env: Env) -> Vec<PendingChange> {
+        let mut changes = Vec::new(&env);
+        for id in timelock::pending_ids(&env).iter() {
+            let change = timelock::get(&env, id);
+            changes.push_back(PendingChange {
+                id,
+                action: change.action,
+                queued_at: change.queued_at,
+                eta: change.eta,
+            });
+        }
+        changes
+    }
+    
+    /// Get the delay between queueing and executing a change
+    pub fn get_timelock_delay(env: Env) -> u64 {
+        timelock::delay(&env)
+    }
+    
+    /// Get the guardian allowed to cancel queued changes
+    pub fn get_guardian(env: Env) -> Address {
+        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
+        timelock::guardian(&env, &admin)
+    }
+    
+    /// Replace the multisig signer set and approval threshold (admin only)
+    pub fn set_signers(env: Env, caller: Address, signers: Vec<Address>, threshold: u32) {
+        Self::require_admin(
+            &env,
+            &caller,
+            (Symbol::new(&env, "set_signers"), signers.clone(), threshold),
+        );
         
-        log!(&env, "Risk parameters updated by admin: {}", caller);
+        multisig::set_signers(&env, &signers, threshold);
+    }
+    
+    /// Approve an admin action by its hash (signers only)
+    pub fn approve_action(env: Env, signer: Address, action_hash: BytesN<32>) -> u32 {
+        multisig::approve(&env, &signer, &action_hash)
+    }
+    
+    /// Withdraw a signer's approval of an action that has not been executed
+    pub fn revoke_approval(env: Env, signer: Address, action_hash: BytesN<32>) {
+        multisig::revoke(&env, &signer, &action_hash);
+    }
+    
+    /// Get the multisig signer set
+    pub fn get_signers(env: Env) -> Vec<Address> {
+        multisig::signers(&env)
+    }
+    
+    /// Get the number of signer approvals each admin action needs (0 when multisig is off)
+    pub fn get_signer_threshold(env: Env) -> u32 {
+        multisig::threshold(&env)
+    }
+    
+    /// Get the signers who approved an action
+    pub fn get_approvals(env: Env, action_hash: BytesN<32>) -> Vec<Address> {
+        multisig::approvals(&env, &action_hash)
     }
     
     // Internal helper functions
     
+    /// Internal helper to authorize an admin action, with signer approvals when multisig is on
+    fn require_admin<T: IntoVal<Env, Val>>(env: &Env, caller: &Address, action: T) {
+        let admin: Address = env.storage().instance().get(&ADMIN).unwrap();
+        multisig::require_admin(env, &admin, caller, action);
+    }
+    
     fn set_default_allocations(env: &Env) {
         let conservative = AssetAllocation {
             kale_percentage: 2000,   // 20%
@@ -334,7 +492,7 @@ impl RiskManagerContract {
             total_volatility += data.daily_volatility;
         }
         
-        total_volatility / market_conditions.len() as u32
+        total_volatility / market_conditions.len()
     }
     
     fn calculate_correlation_risk(_market_conditions: &Vec<VolatilityData>) -> u32 {
@@ -360,6 +518,6 @@ impl RiskManagerContract {
     }
     
     fn abs_diff(a: u32, b: u32) -> u32 {
-        if a > b { a - b } else { b - a }
+        a.abs_diff(b)
     }
 }
@@ -1,11 +1,26 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{testutils::Address as _, Address, Env};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, xdr::ToXdr, Address, Env,
+};
+
+fn setup(env: &Env) -> (RiskManagerContractClient<'_>, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, RiskManagerContract);
+    let client = RiskManagerContractClient::new(env, &contract_id);
+    
+    let admin = Address::generate(env);
+    client.initialize(&admin, &3000, &1000, &7000, &2000, &500);
+    
+    (client, admin)
+}
 
 #[test]
 fn test_initialize_risk_manager() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register_contract(None, RiskManagerContract);
     let client = RiskManagerContractClient::new(&env, &contract_id);
     
@@ -24,17 +39,17 @@ fn test_initialize_risk_manager() {
     assert_eq!(params.max_position_size, 3000);
     assert_eq!(params.max_daily_volatility, 1000);
     assert_eq!(params.correlation_threshold, 7000);
+    
+    // A second initialize cannot replace the admin or parameters
+    let attacker = Address::generate(&env);
+    assert!(client.try_initialize(&attacker, &5000, &5000, &5000, &5000, &5000).is_err());
+    assert_eq!(client.get_risk_parameters().max_position_size, 3000);
 }
 
 #[test]
 fn test_get_allocation_conservative() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, RiskManagerContract);
-    let client = RiskManagerContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
-    
-    client.initialize(&admin, &3000, &1000, &7000, &2000, &500);
+    let (client, _) = setup(&env);
     
     let allocation = client.get_allocation(&RiskProfile::Conservative);
     
@@ -51,12 +66,7 @@ fn test_get_allocation_conservative() {
 #[test]
 fn test_get_allocation_aggressive() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, RiskManagerContract);
-    let client = RiskManagerContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
-    
-    client.initialize(&admin, &3000, &1000, &7000, &2000, &500);
+    let (client, _) = setup(&env);
     
     let allocation = client.get_allocation(&RiskProfile::Aggressive);
     
@@ -73,12 +83,7 @@ fn test_get_allocation_aggressive() {
 #[test]
 fn test_should_rebalance() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, RiskManagerContract);
-    let client = RiskManagerContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
-    
-    client.initialize(&admin, &3000, &1000, &7000, &2000, &500); // 5% threshold
+    let (client, _) = setup(&env); // 5% threshold
     
     // Test allocation that's within threshold (no rebalancing needed)
     let current_allocation = AssetAllocation {
@@ -106,14 +111,7 @@ fn test_should_rebalance() {
 #[test]
 fn test_update_allocation() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, RiskManagerContract);
-    let client = RiskManagerContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
-    
-    client.initialize(&admin, &3000, &1000, &7000, &2000, &500);
-    
-    env.mock_all_auths();
+    let (client, admin) = setup(&env);
     
     // Update conservative allocation
     let new_allocation = AssetAllocation {
@@ -123,7 +121,15 @@ fn test_update_allocation() {
         xlm_percentage: 1000,  // 10%
     };
     
-    client.update_allocation(&admin, &RiskProfile::Conservative, &new_allocation);
+    let change_id = client.update_allocation(&admin, &RiskProfile::Conservative, &new_allocation);
+    
+    // The current allocation stays in force until the timelock expires
+    assert_eq!(client.get_allocation(&RiskProfile::Conservative).kale_percentage, 2000);
+    
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = client.get_timelock_delay();
+    });
+    client.execute_change(&change_id);
     
     let updated = client.get_allocation(&RiskProfile::Conservative);
     assert_eq!(updated.kale_percentage, 1500);
@@ -134,14 +140,7 @@ fn test_update_allocation() {
 #[should_panic(expected = "Allocation percentages must sum to 100%")]
 fn test_update_allocation_invalid_total() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, RiskManagerContract);
-    let client = RiskManagerContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
-    
-    client.initialize(&admin, &3000, &1000, &7000, &2000, &500);
-    
-    env.mock_all_auths();
+    let (client, admin) = setup(&env);
     
     // Invalid allocation that doesn't sum to 100%
     let invalid_allocation = AssetAllocation {
@@ -158,16 +157,9 @@ fn test_update_allocation_invalid_total() {
 #[should_panic(expected = "Unauthorized")]
 fn test_update_allocation_unauthorized() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, RiskManagerContract);
-    let client = RiskManagerContractClient::new(&env, &contract_id);
-    
-    let admin = Address::generate(&env);
+    let (client, _) = setup(&env);
     let unauthorized = Address::generate(&env);
     
-    client.initialize(&admin, &3000, &1000, &7000, &2000, &500);
-    
-    env.mock_all_auths();
-    
     let allocation = AssetAllocation {
         kale_percentage: 2500,
         btc_percentage: 2500,
@@ -177,3 +169,80 @@ fn test_update_allocation_unauthorized() {
     
     client.update_allocation(&unauthorized, &RiskProfile::Conservative, &allocation);
 }
+
+#[test]
+fn test_guardian_cancels_risk_parameter_change() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let guardian = Address::generate(&env);
+    
+    client.set_guardian(&admin, &guardian);
+    let change_id = client.update_risk_parameters(
+        &admin,
+        &RiskParameters {
+            max_position_size: 9000,
+            max_daily_volatility: 5000,
+            correlation_threshold: 9000,
+            stress_test_threshold: 5000,
+            rebalance_threshold: 2000,
+        },
+    );
+    assert_eq!(client.get_pending_changes().len(), 1);
+    
+    client.cancel_change(&guardian, &change_id);
+    assert_eq!(client.get_pending_changes().len(), 0);
+    assert_eq!(client.get_risk_parameters().max_position_size, 3000);
+}
+
+#[test]
+fn test_governor_queues_allocation_change() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let governor = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    
+    let allocation = AssetAllocation {
+        kale_percentage: 2000,
+        btc_percentage: 3000,
+        usdc_percentage: 4000,
+        xlm_percentage: 1000,
+    };
+    assert!(client.try_update_allocation(&governor, &RiskProfile::Moderate, &allocation).is_err());
+    
+    client.set_governor(&admin, &governor);
+    client.update_allocation(&governor, &RiskProfile::Moderate, &allocation);
+    assert_eq!(client.get_pending_changes().len(), 1);
+    assert!(client.try_update_allocation(&stranger, &RiskProfile::Moderate, &allocation).is_err());
+}
+
+#[test]
+fn test_multisig_approvals_gate_risk_parameter_change() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    
+    let signer_a = Address::generate(&env);
+    let signer_b = Address::generate(&env);
+    client.set_signers(&admin, &vec![&env, signer_a.clone(), signer_b.clone()], &2);
+    
+    let params = RiskParameters {
+        max_position_size: 4000,
+        max_daily_volatility: 1500,
+        correlation_threshold: 7000,
+        stress_test_threshold: 2000,
+        rebalance_threshold: 500,
+    };
+    let action_hash: BytesN<32> = env
+        .crypto()
+        .sha256(&(Symbol::new(&env, "update_risk_parameters"), params.clone()).to_xdr(&env))
+        .into();
+    
+    client.approve_action(&signer_a, &action_hash);
+    assert!(client.try_update_risk_parameters(&admin, &params).is_err());
+    
+    client.approve_action(&signer_b, &action_hash);
+    client.update_risk_parameters(&signer_a, &params);
+    
+    // Approvals are consumed by the action
+    assert_eq!(client.get_approvals(&action_hash).len(), 0);
+    assert_eq!(client.get_pending_changes().len(), 1);
+}